@@ -0,0 +1,662 @@
+use std::collections::{HashMap, VecDeque};
+
+use regex::Regex;
+
+use unedged;
+use regex_ast::RegexAst;
+use regex_ast::RegexAst::*;
+
+//The puzzles only ever use these four symbols, so `.` and classes are
+//interpreted relative to them.
+pub const ALPHABET: [char; 4] = ['0', '1', '2', '3'];
+pub const ALPHABET_SIZE: usize = 4;
+
+//State elimination can grow the regex exponentially, so past this many parts
+//`Dfa::to_regex_ast` stops, and the canonicalizer has nothing to write out.
+const MAX_ELIMINATION_SIZE: usize = 2000;
+
+fn symbol_index(c: char) -> Option<usize> {
+    ALPHABET.iter().position(|&a| a == c)
+}
+
+/// A complete deterministic automaton over `ALPHABET`. State `0` is the start state.
+#[derive(Clone, Debug)]
+pub struct Dfa {
+    pub transitions: Vec<[usize; ALPHABET_SIZE]>,
+    pub accepting: Vec<bool>,
+}
+
+impl Dfa {
+    pub fn from_ast(ast: &RegexAst) -> Dfa {
+        let mut nfa = Nfa::new();
+        let (start, accept) = build(&mut nfa, ast);
+
+        nfa.determinize(start, accept)
+    }
+
+    /// Compiles a regex string, with or without `^`/`$` edges, into a `Dfa`.
+    /// Returns `None` if the regex uses syntax outside what the puzzles generate.
+    pub fn from_regex_str(regex: &str) -> Option<Dfa> {
+        RegexAst::parse(regex).ok().map(|ast| Dfa::from_ast(&ast))
+    }
+
+    pub fn len(&self) -> usize {
+        self.transitions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.transitions.is_empty()
+    }
+
+    pub fn accepts(&self, text: &str) -> bool {
+        let mut state = 0;
+
+        for c in text.chars() {
+            match symbol_index(c) {
+                Some(symbol) => state = self.transitions[state][symbol],
+                None => return false,
+            }
+        }
+
+        self.accepting[state]
+    }
+
+    /// Hopcroft–Karp: union states of both automata that must be equivalent
+    /// and fail as soon as a union joins an accepting and a rejecting state.
+    pub fn equivalent(&self, other: &Dfa) -> bool {
+        let offset = self.len();
+
+        let accepting = |i: usize| if i < offset {
+            self.accepting[i]
+        } else {
+            other.accepting[i - offset]
+        };
+        let next = |i: usize, symbol: usize| if i < offset {
+            self.transitions[i][symbol]
+        } else {
+            other.transitions[i - offset][symbol] + offset
+        };
+
+        let mut parents: Vec<usize> = (0..offset + other.len()).collect();
+
+        fn find(parents: &mut [usize], mut i: usize) -> usize {
+            while parents[i] != i {
+                parents[i] = parents[parents[i]];
+                i = parents[i];
+            }
+            i
+        }
+
+        parents[offset] = 0;
+        let mut stack = vec![(0, offset)];
+
+        while let Some((p, q)) = stack.pop() {
+            if accepting(p) != accepting(q) {
+                return false;
+            }
+
+            for symbol in 0..ALPHABET_SIZE {
+                let (p_next, q_next) = (next(p, symbol), next(q, symbol));
+                let p_root = find(&mut parents, p_next);
+                let q_root = find(&mut parents, q_next);
+
+                if p_root != q_root {
+                    parents[q_root] = p_root;
+                    stack.push((p_next, q_next));
+                }
+            }
+        }
+
+        true
+    }
+
+    /// Breadth-first search over pairs of states. Symbols are tried in order,
+    /// so the first disagreement found is the shortlex-first one.
+    pub fn shortest_counterexample(&self, other: &Dfa) -> Option<String> {
+        self.shortest_disagreement(0, other, 0)
+    }
+
+    /// Like `shortest_counterexample`, but starting from state `p` of this
+    /// automaton and state `q` of the other one. With the same automaton
+    /// twice that's the shortest suffix that tells two of its states apart.
+    pub fn shortest_disagreement(&self, p: usize, other: &Dfa, q: usize) -> Option<String> {
+        let mut parents: HashMap<(usize, usize), ((usize, usize), usize)> = HashMap::new();
+        let mut queue = VecDeque::new();
+
+        let start = (p, q);
+        queue.push_back(start);
+
+        while let Some(pair) = queue.pop_front() {
+            let (p, q) = pair;
+
+            if self.accepting[p] != other.accepting[q] {
+                let mut result = Vec::new();
+                let mut current = pair;
+
+                while let Some(&(parent, symbol)) = parents.get(&current) {
+                    result.push(ALPHABET[symbol]);
+                    current = parent;
+                }
+
+                return Some(result.into_iter().rev().collect());
+            }
+
+            for symbol in 0..ALPHABET_SIZE {
+                let next = (self.transitions[p][symbol], other.transitions[q][symbol]);
+
+                if next != start && !parents.contains_key(&next) {
+                    parents.insert(next, (pair, symbol));
+                    queue.push_back(next);
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Moore's partition refinement. The states are then numbered in breadth
+    /// first order so equivalent automata minimize to identical ones.
+    pub fn minimized(&self) -> Dfa {
+        let mut classes: Vec<usize> = self.accepting
+            .iter()
+            .map(|&a| if a { 1 } else { 0 })
+            .collect();
+        let mut class_count = 0;
+
+        loop {
+            let mut signatures: HashMap<(usize, [usize; ALPHABET_SIZE]), usize> = HashMap::new();
+            let mut new_classes = Vec::with_capacity(self.len());
+
+            for (state, row) in self.transitions.iter().enumerate() {
+                let mut targets = [0; ALPHABET_SIZE];
+                for (target, &next) in targets.iter_mut().zip(row.iter()) {
+                    *target = classes[next];
+                }
+
+                let next_index = signatures.len();
+                new_classes.push(*signatures
+                                      .entry((classes[state], targets))
+                                      .or_insert(next_index));
+            }
+
+            classes = new_classes;
+
+            if signatures.len() == class_count {
+                break;
+            }
+            class_count = signatures.len();
+        }
+
+        let mut representatives = vec![None; class_count];
+        for (state, &class) in classes.iter().enumerate() {
+            if representatives[class].is_none() {
+                representatives[class] = Some(state);
+            }
+        }
+
+        let mut numbering: Vec<Option<usize>> = vec![None; class_count];
+        let mut order = vec![classes[0]];
+        numbering[classes[0]] = Some(0);
+
+        let mut current = 0;
+        while current < order.len() {
+            let state = representatives[order[current]].unwrap();
+            for &next in self.transitions[state].iter() {
+                let class = classes[next];
+                if numbering[class].is_none() {
+                    numbering[class] = Some(order.len());
+                    order.push(class);
+                }
+            }
+            current += 1;
+        }
+
+        let mut result = Dfa {
+            transitions: Vec::with_capacity(order.len()),
+            accepting: Vec::with_capacity(order.len()),
+        };
+
+        for &class in order.iter() {
+            let state = representatives[class].unwrap();
+            let mut row = [0; ALPHABET_SIZE];
+            for (target, &next) in row.iter_mut().zip(self.transitions[state].iter()) {
+                *target = numbering[classes[next]].unwrap();
+            }
+
+            result.transitions.push(row);
+            result.accepting.push(self.accepting[state]);
+        }
+
+        result
+    }
+
+    /// States from which an accepting state can be reached.
+    pub fn live_states(&self) -> Vec<bool> {
+        let mut live = self.accepting.clone();
+        let mut changed = true;
+
+        while changed {
+            changed = false;
+            for (state, row) in self.transitions.iter().enumerate() {
+                if !live[state] && row.iter().any(|&next| live[next]) {
+                    live[state] = true;
+                    changed = true;
+                }
+            }
+        }
+
+        live
+    }
+
+    /// State elimination, removing the live states in the given order. States
+    /// missing from `order` are removed afterwards in index order. Returns
+    /// `None` if the language is empty, or if the regex grows past
+    /// `MAX_ELIMINATION_SIZE` parts along the way, since that can happen
+    /// exponentially fast.
+    pub fn to_regex_ast(&self, order: &[usize]) -> Option<RegexAst> {
+        let live = self.live_states();
+        let start = self.len();
+        let end = start + 1;
+
+        let mut edges: Vec<Vec<Option<RegexAst>>> = vec![vec![None; end + 1]; end + 1];
+        //how many parts each edge's regex has
+        let mut sizes = vec![vec![0; end + 1]; end + 1];
+
+        edges[start][0] = Some(Empty);
+        for state in 0..self.len() {
+            if !live[state] {
+                continue;
+            }
+
+            if self.accepting[state] {
+                edges[state][end] = Some(Empty);
+            }
+
+            for next in 0..self.len() {
+                if !live[next] {
+                    continue;
+                }
+
+                let symbols: Vec<u8> = (0..ALPHABET_SIZE)
+                    .filter(|&symbol| self.transitions[state][symbol] == next)
+                    .map(|symbol| symbol as u8)
+                    .collect();
+
+                if !symbols.is_empty() {
+                    edges[state][next] = Some(RegexAst::from_char_set(symbols));
+                    sizes[state][next] = 1;
+                }
+            }
+        }
+
+        let mut remaining: Vec<usize> = (0..self.len()).filter(|&s| live[s]).collect();
+        let mut elimination_order: Vec<usize> = order
+            .iter()
+            .cloned()
+            .filter(|s| remaining.contains(s))
+            .collect();
+        for &state in remaining.iter() {
+            if !elimination_order.contains(&state) {
+                elimination_order.push(state);
+            }
+        }
+
+        for &state in elimination_order.iter() {
+            remaining.retain(|&s| s != state);
+
+            let self_loop = edges[state][state].take().map(star);
+            let self_loop_size = sizes[state][state];
+
+            let sources: Vec<usize> = remaining.iter().cloned().chain(Some(start)).collect();
+            let targets: Vec<usize> = remaining.iter().cloned().chain(Some(end)).collect();
+
+            for &source in sources.iter() {
+                let incoming = match edges[source][state].take() {
+                    Some(incoming) => incoming,
+                    None => continue,
+                };
+
+                for &target in targets.iter() {
+                    if let Some(ref outgoing) = edges[state][target] {
+                        let mut sequence = vec![incoming.clone()];
+                        if let Some(ref l) = self_loop {
+                            sequence.push(l.clone());
+                        }
+                        sequence.push(outgoing.clone());
+
+                        let path = RegexAst::from_sequence(sequence);
+
+                        edges[source][target] = Some(match edges[source][target].take() {
+                            Some(existing) => or(existing, path),
+                            None => path,
+                        });
+
+                        sizes[source][target] += sizes[source][state] + self_loop_size +
+                                                 sizes[state][target] +
+                                                 1;
+                        if sizes[source][target] > MAX_ELIMINATION_SIZE {
+                            return None;
+                        }
+                    }
+                }
+            }
+        }
+
+        edges[start][end].take()
+    }
+}
+
+fn star(regex: RegexAst) -> RegexAst {
+    match regex {
+        Empty => Empty,
+        Star(inner) | Plus(inner) | Optional(inner) => Star(inner),
+        otherwise => Star(Box::new(otherwise)),
+    }
+}
+
+fn or(first: RegexAst, second: RegexAst) -> RegexAst {
+    let mut alternatives = first.alternatives();
+
+    for alternative in second.alternatives() {
+        if !alternatives.contains(&alternative) {
+            alternatives.push(alternative);
+        }
+    }
+
+    RegexAst::from_alternatives(alternatives)
+}
+
+/// Returns whether the two regexes match exactly the same strings over `ALPHABET`.
+/// If either one cannot be compiled, this falls back to comparing the strings.
+pub fn regexes_equivalent(a: &Regex, b: &Regex) -> bool {
+    match (Dfa::from_regex_str(a.as_str()), Dfa::from_regex_str(b.as_str())) {
+        (Some(dfa_a), Some(dfa_b)) => dfa_a.equivalent(&dfa_b),
+        _ => unedged(a.as_str()) == unedged(b.as_str()),
+    }
+}
+
+/// Returns the shortlex-first string that exactly one of the regexes matches,
+/// or `None` if they are equivalent (or either one cannot be compiled).
+pub fn shortest_counterexample(a: &Regex, b: &Regex) -> Option<String> {
+    match (Dfa::from_regex_str(a.as_str()), Dfa::from_regex_str(b.as_str())) {
+        (Some(dfa_a), Some(dfa_b)) => dfa_a.shortest_counterexample(&dfa_b),
+        _ => None,
+    }
+}
+
+struct Nfa {
+    //`None` marks an epsilon move
+    transitions: Vec<Vec<(Option<usize>, usize)>>,
+}
+
+impl Nfa {
+    fn new() -> Self {
+        Nfa { transitions: Vec::new() }
+    }
+
+    fn add_state(&mut self) -> usize {
+        self.transitions.push(Vec::new());
+        self.transitions.len() - 1
+    }
+
+    fn add_move(&mut self, from: usize, symbol: Option<usize>, to: usize) {
+        self.transitions[from].push((symbol, to));
+    }
+
+    fn closure(&self, states: &mut Vec<usize>) {
+        let mut stack = states.clone();
+
+        while let Some(state) = stack.pop() {
+            for &(symbol, to) in self.transitions[state].iter() {
+                if symbol.is_none() && !states.contains(&to) {
+                    states.push(to);
+                    stack.push(to);
+                }
+            }
+        }
+
+        states.sort();
+    }
+
+    fn determinize(&self, start: usize, accept: usize) -> Dfa {
+        let mut start_set = vec![start];
+        self.closure(&mut start_set);
+
+        let mut dfa = Dfa {
+            transitions: Vec::new(),
+            accepting: Vec::new(),
+        };
+        let mut indices: HashMap<Vec<usize>, usize> = HashMap::new();
+        let mut sets = Vec::new();
+
+        indices.insert(start_set.clone(), 0);
+        sets.push(start_set);
+
+        let mut current = 0;
+        while current < sets.len() {
+            let mut row = [0; ALPHABET_SIZE];
+
+            for (symbol, target) in row.iter_mut().enumerate() {
+                let mut next_set = Vec::new();
+                for &state in sets[current].iter() {
+                    for &(s, to) in self.transitions[state].iter() {
+                        if s == Some(symbol) && !next_set.contains(&to) {
+                            next_set.push(to);
+                        }
+                    }
+                }
+                self.closure(&mut next_set);
+
+                *target = match indices.get(&next_set) {
+                    Some(&index) => index,
+                    None => {
+                        let index = sets.len();
+                        indices.insert(next_set.clone(), index);
+                        sets.push(next_set);
+                        index
+                    }
+                };
+            }
+
+            dfa.transitions.push(row);
+            dfa.accepting.push(sets[current].contains(&accept));
+
+            current += 1;
+        }
+
+        dfa
+    }
+}
+
+//Thompson construction. Returns the (start, accept) states of the fragment.
+fn build(nfa: &mut Nfa, ast: &RegexAst) -> (usize, usize) {
+    match *ast {
+        Empty => {
+            let state = nfa.add_state();
+            (state, state)
+        }
+        Digit(_) | Class(_) | Dot => {
+            let start = nfa.add_state();
+            let accept = nfa.add_state();
+            for symbol in ast.char_set().unwrap_or_default() {
+                nfa.add_move(start, Some(symbol as usize), accept);
+            }
+            (start, accept)
+        }
+        Concat(ref items) => {
+            let start = nfa.add_state();
+            let mut end = start;
+            for item in items.iter() {
+                let (s, a) = build(nfa, item);
+                nfa.add_move(end, None, s);
+                end = a;
+            }
+            (start, end)
+        }
+        Or(ref alternatives) => {
+            let start = nfa.add_state();
+            let accept = nfa.add_state();
+            for alternative in alternatives.iter() {
+                let (s, a) = build(nfa, alternative);
+                nfa.add_move(start, None, s);
+                nfa.add_move(a, None, accept);
+            }
+            (start, accept)
+        }
+        Star(ref inner) | Plus(ref inner) | Optional(ref inner) => {
+            let (inner_start, inner_accept) = build(nfa, inner);
+            let start = nfa.add_state();
+            let accept = nfa.add_state();
+
+            nfa.add_move(start, None, inner_start);
+            nfa.add_move(inner_accept, None, accept);
+
+            match *ast {
+                Plus(_) => {
+                    nfa.add_move(inner_accept, None, inner_start);
+                }
+                Optional(_) => {
+                    nfa.add_move(start, None, accept);
+                }
+                _ => {
+                    nfa.add_move(inner_accept, None, inner_start);
+                    nfa.add_move(start, None, accept);
+                }
+            }
+
+            (start, accept)
+        }
+        Group(ref inner) => build(nfa, inner),
+    }
+}
+
+#[cfg(test)]
+mod regexes_equivalent {
+    use super::regexes_equivalent;
+    use edged_regex;
+
+    fn equivalent(a: &str, b: &str) -> bool {
+        regexes_equivalent(&edged_regex(a).unwrap(), &edged_regex(b).unwrap())
+    }
+
+    #[test]
+    fn minimal() {
+        assert!(equivalent("", ""));
+        assert!(!equivalent("", "0"));
+    }
+    #[test]
+    fn one_digit() {
+        assert!(equivalent("0", "0"));
+        assert!(!equivalent("0", "1"));
+    }
+    #[test]
+    fn class_and_or() {
+        assert!(equivalent("0|1", "[01]|1"));
+        assert!(equivalent("[0123]", "."));
+        assert!(!equivalent("0|1", "[012]"));
+    }
+    #[test]
+    fn star_and_plus() {
+        assert!(equivalent("00*", "0+"));
+        assert!(equivalent("0*0*", "0*"));
+        assert!(equivalent("(0*)*", "0*"));
+        assert!(!equivalent("0*", "0+"));
+    }
+    #[test]
+    fn found_example_1() {
+        assert!(equivalent("(1)+|.|1+|[012]*", "3|[012]*"));
+    }
+}
+
+#[cfg(test)]
+mod shortest_counterexample {
+    use super::shortest_counterexample;
+    use edged_regex;
+
+    fn counterexample(a: &str, b: &str) -> Option<String> {
+        shortest_counterexample(&edged_regex(a).unwrap(), &edged_regex(b).unwrap())
+    }
+
+    #[test]
+    fn minimal() {
+        assert_eq!(None, counterexample("", ""));
+        assert_eq!(Some(String::new()), counterexample("", "0"));
+    }
+    #[test]
+    fn equivalent() {
+        assert_eq!(None, counterexample("0|1", "[01]"));
+        assert_eq!(None, counterexample("00*", "0+"));
+    }
+    #[test]
+    fn shortest() {
+        assert_eq!(Some(String::from("00")), counterexample("0", "0+"));
+        assert_eq!(Some(String::new()), counterexample("0+", "0*"));
+    }
+    #[test]
+    fn shortlex_first() {
+        assert_eq!(Some(String::from("0")), counterexample("[01]", "[23]"));
+        assert_eq!(Some(String::from("1")), counterexample("[0123]", "0|[23]"));
+        assert_eq!(Some(String::from("03")), counterexample(".[012]", ".."));
+    }
+}
+
+#[cfg(test)]
+mod minimized {
+    use super::Dfa;
+
+    fn state_count(regex: &str) -> usize {
+        Dfa::from_regex_str(regex).unwrap().minimized().len()
+    }
+
+    #[test]
+    fn minimal() {
+        //the empty string, then the dead state
+        assert_eq!(2, state_count(""));
+    }
+    #[test]
+    fn star() {
+        assert_eq!(3, state_count("."));
+        assert_eq!(1, state_count(".*|0"));
+        assert_eq!(2, state_count("0*"));
+    }
+    #[test]
+    fn equivalent_automata_become_identical() {
+        let first = Dfa::from_regex_str("(1)+|.|1+|[012]*").unwrap().minimized();
+        let second = Dfa::from_regex_str("[012]*|3").unwrap().minimized();
+
+        assert_eq!(first.transitions, second.transitions);
+        assert_eq!(first.accepting, second.accepting);
+    }
+}
+
+#[cfg(test)]
+mod to_regex_ast {
+    use super::Dfa;
+
+    fn round_trip(regex: &str) -> Dfa {
+        let dfa = Dfa::from_regex_str(regex).unwrap().minimized();
+        let ast = dfa.to_regex_ast(&[]).unwrap();
+
+        Dfa::from_ast(&ast)
+    }
+
+    #[test]
+    fn minimal() {
+        assert!(round_trip("").equivalent(&Dfa::from_regex_str("").unwrap()));
+    }
+    #[test]
+    fn keeps_the_language() {
+        for regex in ["0", "[01]*2", "(01|2)+", "3|[012]*", "(0[12]*3)*|1"].iter() {
+            assert!(round_trip(regex).equivalent(&Dfa::from_regex_str(regex).unwrap()),
+                    "{}",
+                    regex);
+        }
+    }
+    #[test]
+    fn gives_up_when_too_big() {
+        //"the fourth to last symbol is 0" has 16 states, all tangled together
+        let dfa = Dfa::from_regex_str(".*0...").unwrap().minimized();
+
+        assert_eq!(None, dfa.to_regex_ast(&[]));
+    }
+}
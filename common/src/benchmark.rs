@@ -0,0 +1,168 @@
+use std::fmt;
+
+use rand::{StdRng, SeedableRng};
+
+use automaton::{shortest_counterexample, Dfa, ALPHABET_SIZE};
+use learner::{LearnerKind, Question, new_learner};
+use {generate_regex, unedged, Example};
+
+/// How one learner did against a scripted master on a run of generated puzzles.
+pub struct BenchmarkReport {
+    pub kind: LearnerKind,
+    pub puzzles: usize,
+    //the seed, the puzzle and how many examples it took
+    pub solved: Vec<(usize, String, usize)>,
+    //the seed and the puzzle
+    pub unsolved: Vec<(usize, String)>,
+}
+
+impl BenchmarkReport {
+    pub fn solve_rate(&self) -> f32 {
+        if self.puzzles == 0 {
+            return 0.0;
+        }
+
+        self.solved.len() as f32 / self.puzzles as f32
+    }
+
+    pub fn median_examples(&self) -> Option<usize> {
+        let mut counts: Vec<usize> = self.solved.iter().map(|&(_, _, count)| count).collect();
+        counts.sort();
+
+        counts.get(counts.len() / 2).cloned()
+    }
+}
+
+impl fmt::Display for BenchmarkReport {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(formatter,
+                 "{}: solved {} of {} ({:.0}%)",
+                 self.kind.name(),
+                 self.solved.len(),
+                 self.puzzles,
+                 self.solve_rate() * 100.0)?;
+
+        match self.median_examples() {
+            Some(median) => writeln!(formatter, "median examples needed: {}", median)?,
+            None => writeln!(formatter, "median examples needed: -")?,
+        }
+
+        for &(seed, ref regex) in self.unsolved.iter() {
+            writeln!(formatter, "never converged: {} (seed {})", regex, seed)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Generates `puzzles` puzzles from the seeds `0..puzzles` and has a master
+/// that shows the shortest matching string, and then always the shortest
+/// example the guess gets wrong, play against a learner of the given kind.
+/// Active learners get their questions answered instead. A puzzle counts as
+/// unsolved once it takes more than `max_examples` examples, or if the learner
+/// ignores an example outright.
+pub fn run_benchmark(kind: LearnerKind, puzzles: usize, max_examples: usize) -> BenchmarkReport {
+    let mut report = BenchmarkReport {
+        kind,
+        puzzles,
+        solved: Vec::new(),
+        unsolved: Vec::new(),
+    };
+
+    let nothing = Dfa {
+        transitions: vec![[0; ALPHABET_SIZE]],
+        accepting: vec![false],
+    };
+
+    for seed in 0..puzzles {
+        let seed_slice: &[_] = &[seed];
+        let mut rng: StdRng = SeedableRng::from_seed(seed_slice);
+        let regex = generate_regex(&mut rng);
+
+        let mut learner = new_learner(kind);
+        let mut examples: Vec<Example> = Vec::new();
+        let mut result = None;
+
+        //like in Zendo, the master starts by showing something that matches,
+        //since none of the guesses can say that nothing does
+        if let Some(dfa) = Dfa::from_regex_str(regex.as_str()) {
+            if let Some(text) = dfa.shortest_counterexample(&nothing) {
+                examples.push(Example::new(&text, &regex));
+                learner.observe(examples.last().unwrap());
+            }
+        }
+
+        while examples.len() <= max_examples {
+            let text = match learner.question() {
+                Some(Question::IsMember(text)) => text,
+                Some(Question::IsEquivalent) |
+                None => {
+                    //it has seen a matching example, so having nothing to
+                    //guess means it has given up
+                    let guess = match learner.hypothesis() {
+                        Some(guess) => guess,
+                        None => break,
+                    };
+
+                    match shortest_counterexample(&regex, &guess) {
+                        Some(text) => text,
+                        None => {
+                            result = Some(examples.len());
+                            break;
+                        }
+                    }
+                }
+            };
+
+            //if the learner is still wrong about an example it has already
+            //seen then showing it again won't help
+            if examples.iter().any(|e| e.text == text) {
+                break;
+            }
+
+            examples.push(Example::new(&text, &regex));
+            learner.observe(examples.last().unwrap());
+        }
+
+        let puzzle = unedged(regex.as_str()).to_string();
+        match result {
+            Some(count) => report.solved.push((seed, puzzle, count)),
+            None => report.unsolved.push((seed, puzzle)),
+        }
+    }
+
+    report
+}
+
+#[cfg(test)]
+mod run_benchmark {
+    use super::*;
+
+    #[test]
+    fn minimal() {
+        let report = run_benchmark(LearnerKind::Rpni, 0, 10);
+
+        assert_eq!(0, report.puzzles);
+        assert_eq!(None, report.median_examples());
+    }
+    #[test]
+    fn counts_every_puzzle() {
+        let report = run_benchmark(LearnerKind::Enumerative, 5, 50);
+
+        assert_eq!(5, report.solved.len() + report.unsolved.len());
+    }
+    //takes a while, so run it with
+    //`cargo test -p common --release -- --ignored --nocapture`
+    #[test]
+    #[ignore]
+    fn every_learner() {
+        let kinds = [LearnerKind::Heuristic,
+                     LearnerKind::Rpni,
+                     LearnerKind::LStar,
+                     LearnerKind::Enumerative];
+
+        for &kind in kinds.iter() {
+            println!("{}", run_benchmark(kind, 100, 200));
+        }
+    }
+}
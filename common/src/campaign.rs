@@ -0,0 +1,140 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use {Grammar, FULL_GRAMMAR};
+
+/// Where `save_progress` keeps the campaign progress, relative to wherever
+/// the game is run from.
+pub const PROGRESS_FILE: &str = "campaign_progress.txt";
+
+pub struct Level {
+    pub name: &'static str,
+    pub grammar: Grammar,
+}
+
+const DIGITS: Grammar = Grammar {
+    max_depth: 0,
+    classes: false,
+    dot: false,
+    or: false,
+    groups: false,
+};
+
+/// The campaign, easiest first. Each level allows one more part of the regex
+/// syntax, or lets it nest one level deeper, than the one before it, ending
+/// with the puzzles the rest of the game uses.
+pub const LEVELS: [Level; 8] = [Level {
+                                    name: "Digits",
+                                    grammar: DIGITS,
+                                },
+                                Level {
+                                    name: "Classes",
+                                    grammar: Grammar { classes: true, ..DIGITS },
+                                },
+                                Level {
+                                    name: "Anything",
+                                    grammar: Grammar {
+                                        classes: true,
+                                        dot: true,
+                                        ..DIGITS
+                                    },
+                                },
+                                Level {
+                                    name: "Or",
+                                    grammar: Grammar {
+                                        classes: true,
+                                        dot: true,
+                                        or: true,
+                                        ..DIGITS
+                                    },
+                                },
+                                Level {
+                                    name: "More or",
+                                    grammar: Grammar {
+                                        max_depth: 1,
+                                        groups: false,
+                                        ..FULL_GRAMMAR
+                                    },
+                                },
+                                Level {
+                                    name: "Groups",
+                                    grammar: Grammar { max_depth: 1, ..FULL_GRAMMAR },
+                                },
+                                Level {
+                                    name: "Nesting",
+                                    grammar: Grammar { max_depth: 2, ..FULL_GRAMMAR },
+                                },
+                                Level {
+                                    name: "Everything",
+                                    grammar: FULL_GRAMMAR,
+                                }];
+
+/// How many levels have been finished. Missing or unreadable progress means
+/// none have, and the count is never more than the number of levels.
+pub fn load_progress(path: &Path) -> usize {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|text| text.trim().parse().ok())
+        .map(|finished: usize| finished.min(LEVELS.len()))
+        .unwrap_or(0)
+}
+
+/// Records that `level` has been finished, unless a later one already was.
+/// Returns the number of finished levels.
+pub fn save_progress(path: &Path, level: usize) -> io::Result<usize> {
+    let finished = load_progress(path).max(level + 1).min(LEVELS.len());
+
+    fs::write(path, finished.to_string())?;
+
+    Ok(finished)
+}
+
+#[cfg(test)]
+mod levels {
+    use super::*;
+    use rand::{StdRng, SeedableRng};
+    use {generate_regex_with, unedged};
+
+    fn generated(grammar: &Grammar) -> Vec<String> {
+        let seed: &[_] = &[42];
+        let mut rng: StdRng = SeedableRng::from_seed(seed);
+
+        (0..50)
+            .map(|_| unedged(generate_regex_with(&mut rng, grammar).as_str()).to_string())
+            .collect()
+    }
+
+    #[test]
+    fn minimal() {
+        for regex in generated(&LEVELS[0].grammar) {
+            assert!(regex.chars().all(|c| "0123*+".contains(c)), "{}", regex);
+        }
+    }
+    #[test]
+    fn no_groups_before_they_are_unlocked() {
+        for level in LEVELS.iter().filter(|level| !level.grammar.groups) {
+            for regex in generated(&level.grammar) {
+                assert!(!regex.contains('('), "{}", regex);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod save_progress {
+    use super::*;
+
+    #[test]
+    fn never_goes_backwards() {
+        let path = std::env::temp_dir().join("rezendo_master_campaign_progress_test.txt");
+        let _ = fs::remove_file(&path);
+
+        assert_eq!(0, load_progress(&path));
+        assert_eq!(3, save_progress(&path, 2).unwrap());
+        assert_eq!(3, save_progress(&path, 0).unwrap());
+        assert_eq!(3, load_progress(&path));
+
+        let _ = fs::remove_file(&path);
+    }
+}
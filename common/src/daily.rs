@@ -0,0 +1,195 @@
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::Path;
+
+use rand::{StdRng, SeedableRng};
+use regex::Regex;
+
+use generate_regex;
+use score::difficulty;
+
+/// Where `record_result` keeps the daily results, relative to wherever the
+/// game is run from.
+pub const RESULTS_FILE: &str = "daily_results.txt";
+
+//Most generated puzzles are things like `.` or `1*`, which are over as soon
+//as they start. The bounds are on `difficulty`.
+const MIN_DIFFICULTY: usize = 4;
+const MAX_DIFFICULTY: usize = 8;
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// Days since the Unix epoch, in UTC, so everyone gets the same day at the
+/// same time.
+pub fn today() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|dur| dur.as_secs() / SECONDS_PER_DAY)
+        .unwrap_or(0)
+}
+
+/// The day as `YYYY-MM-DD`.
+pub fn date_string(day: u64) -> String {
+    //Howard Hinnant's `civil_from_days`, with eras of 400 years starting on
+    //the 1st of March
+    let days = day as i64 + 719_468;
+    let era = days / 146_097;
+    let day_of_era = days - era * 146_097;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 -
+                       day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+
+    let day_of_month = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!("{:04}-{:02}-{:02}", year, month, day_of_month)
+}
+
+/// The puzzle for the given day. It is the first generated regex, with the
+/// day as the seed, that isn't too easy or too hard.
+pub fn daily_regex(day: u64) -> Regex {
+    let seed: &[_] = &[day as usize];
+    let mut rng: StdRng = SeedableRng::from_seed(seed);
+
+    loop {
+        let regex = generate_regex(&mut rng);
+
+        if (MIN_DIFFICULTY..=MAX_DIFFICULTY).contains(&difficulty(&regex)) {
+            return regex;
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DailyResult {
+    pub date: String,
+    //the name of the learner that was taught
+    pub learner: String,
+    pub examples: usize,
+}
+
+/// Every result recorded so far. A missing file just means there aren't any
+/// yet. Reading stops at the first line that can't be read, and lines that
+/// aren't results are skipped.
+pub fn load_results(path: &Path) -> Vec<DailyResult> {
+    let file = match File::open(path) {
+        Ok(file) => file,
+        Err(_) => return Vec::new(),
+    };
+
+    BufReader::new(file)
+        .lines()
+        .map_while(Result::ok)
+        .filter_map(|line| parse_result(&line))
+        .collect()
+}
+
+fn parse_result(line: &str) -> Option<DailyResult> {
+    let mut parts = line.split_whitespace();
+
+    let date = parts.next()?.to_string();
+    let learner = parts.next()?.to_string();
+    let examples = parts.next()?.parse().ok()?;
+
+    Some(DailyResult {
+             date,
+             learner,
+             examples,
+         })
+}
+
+/// Adds the result to the file, unless there already is one for that date
+/// and learner, since only the first try is a fair comparison. Returns the
+/// result that ends up counting.
+pub fn record_result(path: &Path, result: DailyResult) -> io::Result<DailyResult> {
+    if let Some(earlier) = load_results(path)
+           .into_iter()
+           .find(|r| r.date == result.date && r.learner == result.learner) {
+        return Ok(earlier);
+    }
+
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file,
+             "{} {} {}",
+             result.date,
+             result.learner,
+             result.examples)?;
+
+    Ok(result)
+}
+
+#[cfg(test)]
+mod date_string {
+    use super::*;
+
+    #[test]
+    fn minimal() {
+        assert_eq!("1970-01-01", date_string(0));
+    }
+    #[test]
+    fn leap_years() {
+        assert_eq!("2000-02-29", date_string(11_016));
+        assert_eq!("2000-03-01", date_string(11_017));
+        assert_eq!("2026-10-18", date_string(20_744));
+    }
+}
+
+#[cfg(test)]
+mod daily_regex {
+    use super::*;
+
+    #[test]
+    fn same_puzzle_all_day() {
+        assert_eq!(daily_regex(20_744).as_str(), daily_regex(20_744).as_str());
+    }
+    #[test]
+    fn not_too_easy() {
+        for day in 20_000..20_020 {
+            assert!((MIN_DIFFICULTY..=MAX_DIFFICULTY).contains(&difficulty(&daily_regex(day))));
+        }
+    }
+}
+
+#[cfg(test)]
+mod record_result {
+    use super::*;
+
+    #[test]
+    fn first_try_counts() {
+        let path = std::env::temp_dir().join("rezendo_master_daily_results_test.txt");
+        let _ = std::fs::remove_file(&path);
+
+        let result = |learner: &str, examples| {
+            DailyResult {
+                date: "2026-10-18".to_string(),
+                learner: learner.to_string(),
+                examples,
+            }
+        };
+
+        assert_eq!(result("RPNI", 7), record_result(&path, result("RPNI", 7)).unwrap());
+        assert_eq!(result("RPNI", 7), record_result(&path, result("RPNI", 3)).unwrap());
+        assert_eq!(result("L*", 20), record_result(&path, result("L*", 20)).unwrap());
+
+        assert_eq!(vec![result("RPNI", 7), result("L*", 20)], load_results(&path));
+
+        let _ = std::fs::remove_file(&path);
+    }
+}
+
+#[cfg(test)]
+mod load_results {
+    use super::*;
+
+    #[test]
+    fn unreadable_file() {
+        //opening a directory works, but every read of it fails
+        assert_eq!(Vec::<DailyResult>::new(), load_results(&std::env::temp_dir()));
+    }
+}
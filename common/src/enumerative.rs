@@ -0,0 +1,341 @@
+use std::collections::{HashMap, HashSet};
+
+use regex::Regex;
+
+use learner::{Learner, make_consistent};
+use regex_ast::RegexAst;
+use simplify::canonicalize;
+use {edged_regex, Example};
+
+//regexes longer than this aren't searched. Generated puzzles are rarely
+//longer than half of this once simplified.
+const MAX_LENGTH: usize = 24;
+
+/// Searches the regexes `generate_regex` can produce, shortest first, for one
+/// that gets every example right. If there isn't one short enough, it repairs
+/// its last guess the way the heuristic learner does instead.
+pub struct EnumerativeLearner {
+    examples: Vec<Example>,
+    guessed_regex: Option<Regex>,
+}
+
+impl EnumerativeLearner {
+    pub fn new() -> Self {
+        EnumerativeLearner {
+            examples: Vec::new(),
+            guessed_regex: None,
+        }
+    }
+}
+
+impl Default for EnumerativeLearner {
+    fn default() -> Self {
+        EnumerativeLearner::new()
+    }
+}
+
+impl Learner for EnumerativeLearner {
+    fn observe(&mut self, example: &Example) {
+        self.examples.push(example.clone());
+
+        let guessed_regex = match smallest_consistent(&self.examples) {
+            Some(smallest) => canonicalize(smallest),
+            None => {
+                let previous = self.guessed_regex
+                    .as_ref()
+                    .and_then(|regex| RegexAst::parse(regex.as_str()).ok())
+                    .unwrap_or(RegexAst::Empty);
+
+                make_consistent(previous, &self.examples)
+            }
+        };
+
+        if let Ok(regex) = edged_regex(&guessed_regex.to_string()) {
+            self.guessed_regex = Some(regex);
+        }
+    }
+
+    fn hypothesis(&self) -> Option<Regex> {
+        self.guessed_regex.clone()
+    }
+}
+
+/// The shortest regex in the generated grammar that matches exactly the
+/// matched examples, if there is one no longer than `MAX_LENGTH`.
+///
+/// Regexes that agree on every example are interchangeable as far as the
+/// search is concerned, so only the first one found of each kind is kept.
+/// The grammar has no concatenation, so whether a repeated group matches a
+/// string only depends on which substrings of it the group matches. That
+/// means the agreement has to be checked on every substring of the examples,
+/// not just the examples themselves, for dropping the longer ones to be safe.
+pub fn smallest_consistent(examples: &[Example]) -> Option<RegexAst> {
+    let substrings = Substrings::new(examples);
+
+    let target: Vec<(usize, bool)> = examples
+        .iter()
+        .map(|e| (substrings.index[&e.text], e.matched))
+        .collect();
+
+    let consistent = |term: &Term| target.iter().all(|&(i, matched)| term.matches[i] == matched);
+
+    let mut seen = HashSet::new();
+    //`bank[length]` holds the kept regexes of that length
+    let mut bank: Vec<Vec<Term>> = vec![Vec::new(); MAX_LENGTH + 1];
+
+    for length in 1..MAX_LENGTH + 1 {
+        let mut candidates = substrings.leaves(length);
+
+        //`a|b`
+        for left in 1..length {
+            let right = length - 1 - left;
+            if right < left {
+                break;
+            }
+
+            for (i, a) in bank[left].iter().enumerate() {
+                let start = if left == right { i + 1 } else { 0 };
+
+                for b in bank[right][start..].iter() {
+                    let mut alternatives = a.ast.alternatives();
+                    alternatives.extend(b.ast.alternatives());
+
+                    candidates.push(Term {
+                                        ast: RegexAst::from_alternatives(alternatives),
+                                        matches: a.matches
+                                            .iter()
+                                            .zip(b.matches.iter())
+                                            .map(|(&x, &y)| x || y)
+                                            .collect(),
+                                    });
+                }
+            }
+        }
+
+        //`(a)*` and `(a)+`, only for groups that can't match the empty
+        //string, like `generate_regex_helper`
+        if length > 3 {
+            for inner in bank[length - 3].iter().filter(|t| !t.matches[0]) {
+                let group = RegexAst::Group(Box::new(inner.ast.clone()));
+                let plus = substrings.plus(&inner.matches);
+                let mut star = plus.clone();
+                star[0] = true;
+
+                candidates.push(Term {
+                                    ast: RegexAst::Star(Box::new(group.clone())),
+                                    matches: star,
+                                });
+                candidates.push(Term {
+                                    ast: RegexAst::Plus(Box::new(group)),
+                                    matches: plus,
+                                });
+            }
+        }
+
+        for term in candidates {
+            if seen.contains(&term.matches) {
+                continue;
+            }
+
+            if consistent(&term) {
+                return Some(term.ast);
+            }
+
+            seen.insert(term.matches.clone());
+            bank[length].push(term);
+        }
+    }
+
+    None
+}
+
+#[derive(Clone)]
+struct Term {
+    ast: RegexAst,
+    //whether the regex matches each of the substrings
+    matches: Vec<bool>,
+}
+
+//every substring of the examples, shortest first, so the empty string is always `0`
+struct Substrings {
+    texts: Vec<String>,
+    index: HashMap<String, usize>,
+}
+
+impl Substrings {
+    fn new(examples: &[Example]) -> Self {
+        let mut set = HashSet::new();
+        set.insert(String::new());
+
+        for e in examples.iter() {
+            for start in 0..e.text.len() {
+                for end in start + 1..e.text.len() + 1 {
+                    set.insert(e.text[start..end].to_string());
+                }
+            }
+        }
+
+        let mut texts: Vec<String> = set.into_iter().collect();
+        texts.sort_by(|a, b| (a.len(), a).cmp(&(b.len(), b)));
+
+        let index = texts
+            .iter()
+            .enumerate()
+            .map(|(i, t)| (t.clone(), i))
+            .collect();
+
+        Substrings { texts, index }
+    }
+
+    //the digits, classes and `.` with their repetitions, written with `length` characters
+    fn leaves(&self, length: usize) -> Vec<Term> {
+        let mut result = Vec::new();
+
+        for digits in char_sets() {
+            let set = RegexAst::from_char_set(digits.clone());
+            let set_length = set.to_string().len();
+
+            let in_set = |text: &str| text.bytes().all(|b| digits.contains(&b.wrapping_sub(b'0')));
+
+            if set_length == length {
+                result.push(Term {
+                                matches: self.texts
+                                    .iter()
+                                    .map(|t| t.len() == 1 && in_set(t))
+                                    .collect(),
+                                ast: set.clone(),
+                            });
+            }
+
+            //`generate_regex_helper` never repeats a bare `.`
+            if set_length + 1 == length && set != RegexAst::Dot {
+                result.push(Term {
+                                matches: self.texts.iter().map(|t| in_set(t)).collect(),
+                                ast: RegexAst::Star(Box::new(set.clone())),
+                            });
+                result.push(Term {
+                                matches: self.texts
+                                    .iter()
+                                    .map(|t| !t.is_empty() && in_set(t))
+                                    .collect(),
+                                ast: RegexAst::Plus(Box::new(set)),
+                            });
+            }
+        }
+
+        result
+    }
+
+    //which substrings are one or more matches of a regex matching `matches`
+    fn plus(&self, matches: &[bool]) -> Vec<bool> {
+        let mut result = vec![false; self.texts.len()];
+
+        //shorter substrings come first, so their results are ready in time
+        for (i, text) in self.texts.iter().enumerate() {
+            result[i] = matches[i] ||
+                        (1..text.len()).any(|split| {
+                                                matches[self.index[&text[..split]]] &&
+                                                result[self.index[&text[split..]]]
+                                            });
+        }
+
+        result
+    }
+}
+
+//every set of digits `generate_regex_helper` can put in a single position
+fn char_sets() -> Vec<Vec<u8>> {
+    let mut result: Vec<Vec<u8>> = (0..4).map(|d| vec![d]).collect();
+
+    for bits in 0..16u8 {
+        let digits: Vec<u8> = (0..4).filter(|d| bits & (1 << d) != 0).collect();
+
+        if digits.len() > 1 {
+            result.push(digits);
+        }
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod smallest_consistent {
+    use super::*;
+
+    fn labelled(examples: &[(&str, bool)]) -> Vec<Example> {
+        examples
+            .iter()
+            .map(|&(text, matched)| {
+                     Example {
+                         text: text.to_owned(),
+                         matched,
+                     }
+                 })
+            .collect()
+    }
+
+    fn smallest(examples: &[(&str, bool)]) -> Option<String> {
+        smallest_consistent(&labelled(examples)).map(|ast| ast.to_string())
+    }
+
+    #[test]
+    fn minimal() {
+        assert_eq!(Some("0".to_string()), smallest(&[("0", true)]));
+    }
+    #[test]
+    fn found_example_from_readme() {
+        let examples = [("", true), ("0", true), ("11", true), ("012", true), ("3", false),
+                        ("13", false), ("30", false)];
+
+        assert_eq!(Some("[012]*".to_string()), smallest(&examples));
+    }
+    #[test]
+    fn groups() {
+        let examples = [("0", true), ("01", false), ("00", true), ("", false)];
+
+        assert_eq!(Some("0+".to_string()), smallest(&examples));
+    }
+    #[test]
+    fn nothing_fits() {
+        //there's no concatenation, so nothing matches `01` without `10`
+        let examples = [("01", true), ("10", false)];
+
+        assert_eq!(None, smallest(&examples));
+    }
+}
+
+#[cfg(test)]
+mod enumerative_learner {
+    use super::*;
+    use {misclassified, unedged};
+
+    fn taught(examples: &[(&str, bool)]) -> EnumerativeLearner {
+        let mut learner = EnumerativeLearner::new();
+
+        for &(text, matched) in examples.iter() {
+            learner.observe(&Example {
+                                 text: text.to_owned(),
+                                 matched,
+                             });
+        }
+
+        learner
+    }
+
+    #[test]
+    fn guesses_in_the_normal_form() {
+        //the search finds `(.)*`
+        let learner = taught(&[("", true), ("0", true), ("123", true)]);
+
+        assert_eq!(".*", unedged(learner.hypothesis().unwrap().as_str()));
+    }
+    #[test]
+    fn fits_the_examples_when_the_search_fails() {
+        //the generated regexes can't put one digit after another
+        let examples = [("01", true), ("0", false), ("1", false), ("10", false)];
+        let learner = taught(&examples);
+
+        assert!(smallest_consistent(&learner.examples).is_none());
+        assert!(misclassified(&learner.hypothesis().unwrap(), &learner.examples).is_empty());
+    }
+}
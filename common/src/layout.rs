@@ -0,0 +1,240 @@
+use {Point, Rect, Size};
+
+/// How much room one part of a `row` or `column` gets.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Constraint {
+    /// Exactly this many cells, or whatever is left if that's fewer.
+    Length(i32),
+    /// A share of the room the `Length`s leave, in proportion to the other
+    /// `Fill`s' weights.
+    Fill(i32),
+}
+
+/// Which part of an area `anchored` puts something in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Anchor {
+    TopLeft,
+    Top,
+    TopRight,
+    Left,
+    Center,
+    Right,
+    BottomLeft,
+    Bottom,
+    BottomRight,
+}
+
+/// The whole window, for a window of the given size.
+pub fn screen(size: Size) -> Rect {
+    Rect::from_size(Point::new(0, 0), size)
+}
+
+/// `area` with `padding` cells taken off every side. It can shrink down to
+/// nothing, but never turns inside out.
+pub fn padded(area: Rect, padding: i32) -> Rect {
+    let horizontal = padding.min(area.size.width / 2);
+    let vertical = padding.min(area.size.height / 2);
+
+    Rect::from_values(area.top_left.x + horizontal,
+                      area.top_left.y + vertical,
+                      area.size.width - 2 * horizontal,
+                      area.size.height - 2 * vertical)
+}
+
+/// Splits `area` into side by side parts, left to right, with `gap` cells
+/// between them. There is one part for each constraint.
+pub fn row(area: Rect, constraints: &[Constraint], gap: i32) -> Vec<Rect> {
+    split(area.size.width, constraints, gap)
+        .into_iter()
+        .map(|(offset, width)| {
+                 Rect::from_values(area.top_left.x + offset,
+                                   area.top_left.y,
+                                   width,
+                                   area.size.height)
+             })
+        .collect()
+}
+
+/// Splits `area` into parts stacked top to bottom, with `gap` cells between
+/// them. There is one part for each constraint.
+pub fn column(area: Rect, constraints: &[Constraint], gap: i32) -> Vec<Rect> {
+    split(area.size.height, constraints, gap)
+        .into_iter()
+        .map(|(offset, height)| {
+                 Rect::from_values(area.top_left.x,
+                                   area.top_left.y + offset,
+                                   area.size.width,
+                                   height)
+             })
+        .collect()
+}
+
+/// A rect of the given size, or as much of it as fits, in the `anchor` part
+/// of `area`.
+pub fn anchored(area: Rect, size: Size, anchor: Anchor) -> Rect {
+    use self::Anchor::*;
+
+    let width = size.width.min(area.size.width);
+    let height = size.height.min(area.size.height);
+    let spare_width = area.size.width - width;
+    let spare_height = area.size.height - height;
+
+    let x = match anchor {
+        TopLeft | Left | BottomLeft => 0,
+        Top | Center | Bottom => spare_width / 2,
+        TopRight | Right | BottomRight => spare_width,
+    };
+    let y = match anchor {
+        TopLeft | Top | TopRight => 0,
+        Left | Center | Right => spare_height / 2,
+        BottomLeft | Bottom | BottomRight => spare_height,
+    };
+
+    Rect::from_values(area.top_left.x + x, area.top_left.y + y, width, height)
+}
+
+//The (offset, length) of each part of a line `total` cells long. Parts that
+//don't fit get squashed down to nothing at the end of the line.
+fn split(total: i32, constraints: &[Constraint], gap: i32) -> Vec<(i32, i32)> {
+    let gaps = gap * (constraints.len() as i32 - 1).max(0);
+    let mut left_over = (total - gaps).max(0);
+
+    let mut lengths: Vec<i32> = constraints
+        .iter()
+        .map(|&constraint| match constraint {
+                 Constraint::Length(length) => {
+                     let length = length.max(0).min(left_over);
+                     left_over -= length;
+                     length
+                 }
+                 Constraint::Fill(_) => 0,
+             })
+        .collect();
+
+    let total_weight: i32 = constraints
+        .iter()
+        .map(|&constraint| match constraint {
+                 Constraint::Fill(weight) => weight.max(0),
+                 Constraint::Length(_) => 0,
+             })
+        .sum();
+
+    //handing out the running total, rather than each share, means the
+    //rounding never loses a cell
+    let mut weight_so_far = 0;
+    let mut handed_out = 0;
+    for (length, &constraint) in lengths.iter_mut().zip(constraints.iter()) {
+        if let Constraint::Fill(weight) = constraint {
+            weight_so_far += weight.max(0);
+
+            let due = left_over * weight_so_far / total_weight.max(1);
+            *length = due - handed_out;
+            handed_out = due;
+        }
+    }
+
+    let mut offset = 0;
+    lengths
+        .into_iter()
+        .map(|length| {
+                 let part = (offset.min(total), length);
+                 offset += length + gap;
+                 part
+             })
+        .collect()
+}
+
+#[cfg(test)]
+mod row {
+    use super::*;
+    use super::Constraint::*;
+
+    #[test]
+    fn minimal() {
+        let area = Rect::from_values(0, 0, 10, 3);
+
+        assert_eq!(vec![Rect::from_values(0, 0, 10, 3)], row(area, &[Fill(1)], 0));
+    }
+    #[test]
+    fn lengths_then_fills() {
+        let area = Rect::from_values(2, 1, 80, 3);
+
+        assert_eq!(vec![Rect::from_values(2, 1, 15, 3),
+                        Rect::from_values(19, 1, 36, 3),
+                        Rect::from_values(57, 1, 25, 3)],
+                   row(area, &[Length(15), Fill(1), Length(25)], 2));
+    }
+    #[test]
+    fn fills_share_by_weight() {
+        let area = Rect::from_values(0, 0, 10, 1);
+
+        let widths: Vec<i32> = row(area, &[Fill(1), Fill(2), Fill(1)], 0)
+            .iter()
+            .map(|rect| rect.size.width)
+            .collect();
+
+        assert_eq!(vec![2, 5, 3], widths);
+    }
+    #[test]
+    fn too_small() {
+        let area = Rect::from_values(0, 0, 10, 1);
+
+        assert_eq!(vec![Rect::from_values(0, 0, 6, 1),
+                        Rect::from_values(8, 0, 0, 1),
+                        Rect::from_values(10, 0, 0, 1)],
+                   row(area, &[Length(8), Length(8), Fill(1)], 2));
+    }
+}
+
+#[cfg(test)]
+mod column {
+    use super::*;
+    use super::Constraint::*;
+
+    #[test]
+    fn minimal() {
+        let area = Rect::from_values(5, 5, 10, 30);
+
+        assert_eq!(vec![Rect::from_values(5, 5, 10, 26), Rect::from_values(5, 32, 10, 3)],
+                   column(area, &[Fill(1), Length(3)], 1));
+    }
+}
+
+#[cfg(test)]
+mod anchored {
+    use super::*;
+
+    #[test]
+    fn minimal() {
+        let area = Rect::from_values(10, 10, 20, 10);
+
+        assert_eq!(Rect::from_values(10, 10, 4, 2),
+                   anchored(area, Size::new(4, 2), Anchor::TopLeft));
+        assert_eq!(Rect::from_values(18, 14, 4, 2),
+                   anchored(area, Size::new(4, 2), Anchor::Center));
+        assert_eq!(Rect::from_values(26, 18, 4, 2),
+                   anchored(area, Size::new(4, 2), Anchor::BottomRight));
+    }
+    #[test]
+    fn too_big() {
+        let area = Rect::from_values(10, 10, 20, 10);
+
+        assert_eq!(area, anchored(area, Size::new(40, 40), Anchor::Bottom));
+    }
+}
+
+#[cfg(test)]
+mod padded {
+    use super::*;
+
+    #[test]
+    fn minimal() {
+        assert_eq!(Rect::from_values(1, 1, 78, 28),
+                   padded(screen(Size::new(80, 30)), 1));
+    }
+    #[test]
+    fn too_small() {
+        assert_eq!(Rect::from_values(1, 2, 1, 1),
+                   padded(Rect::from_values(0, 2, 3, 1), 1));
+    }
+}
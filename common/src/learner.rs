@@ -0,0 +1,327 @@
+use regex::Regex;
+
+use automaton::Dfa;
+use enumerative::EnumerativeLearner;
+use lstar::LStarLearner;
+use regex_ast::RegexAst;
+use rpni::{RpniLearner, rpni};
+use simplify::{canonicalize, dfa_to_regex};
+use {edged_regex, Example};
+
+/// A computer player. It is shown the master's examples one at a time and
+/// can be asked for its current guess at any point.
+pub trait Learner {
+    fn observe(&mut self, example: &Example);
+
+    /// `None` until the learner has something to guess.
+    fn hypothesis(&self) -> Option<Regex>;
+
+    /// Active learners ask the master things instead of waiting for examples.
+    /// The answer comes back through `observe`.
+    fn question(&mut self) -> Option<Question> {
+        None
+    }
+
+    /// Whether the learner has stopped because its guess got too big to write
+    /// out as a regex.
+    fn gave_up(&self) -> bool {
+        false
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Question {
+    /// Does the hidden regex match this string?
+    IsMember(String),
+    /// Is `hypothesis` the hidden regex? If not, the master should reply with
+    /// an example it gets wrong.
+    IsEquivalent,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LearnerKind {
+    Heuristic,
+    Rpni,
+    LStar,
+    Enumerative,
+}
+
+impl LearnerKind {
+    pub fn name(&self) -> &'static str {
+        match *self {
+            LearnerKind::Heuristic => "Heuristic",
+            LearnerKind::Rpni => "RPNI",
+            LearnerKind::LStar => "L*",
+            LearnerKind::Enumerative => "Smallest",
+        }
+    }
+
+    /// Whether this kind asks the master questions, rather than only learning
+    /// from the examples it is shown.
+    pub fn is_active(&self) -> bool {
+        *self == LearnerKind::LStar
+    }
+
+    pub fn next(&self) -> LearnerKind {
+        match *self {
+            LearnerKind::Heuristic => LearnerKind::Rpni,
+            LearnerKind::Rpni => LearnerKind::LStar,
+            LearnerKind::LStar => LearnerKind::Enumerative,
+            LearnerKind::Enumerative => LearnerKind::Heuristic,
+        }
+    }
+}
+
+pub fn new_learner(kind: LearnerKind) -> Box<dyn Learner> {
+    match kind {
+        LearnerKind::Heuristic => Box::new(HeuristicLearner::new()),
+        LearnerKind::Rpni => Box::new(RpniLearner::new()),
+        LearnerKind::LStar => Box::new(LStarLearner::new()),
+        LearnerKind::Enumerative => Box::new(EnumerativeLearner::new()),
+    }
+}
+
+/// The original computer player. It starts from the positive examples and then
+/// stretches or shrinks the alternatives of its guess to fit each new example.
+pub struct HeuristicLearner {
+    examples: Vec<Example>,
+    guessed_regex: Option<Regex>,
+}
+
+impl HeuristicLearner {
+    pub fn new() -> Self {
+        HeuristicLearner {
+            examples: Vec::new(),
+            guessed_regex: None,
+        }
+    }
+}
+
+impl Default for HeuristicLearner {
+    fn default() -> Self {
+        HeuristicLearner::new()
+    }
+}
+
+impl Learner for HeuristicLearner {
+    fn observe(&mut self, example: &Example) {
+        self.examples.push(example.clone());
+
+        let guessed_regex = match self.guessed_regex {
+            Some(ref previous) => {
+                let previous = RegexAst::parse(previous.as_str()).unwrap_or(RegexAst::Empty);
+
+                fit(&previous, example)
+            }
+            None => positive_alternatives(&self.examples),
+        };
+
+        let guessed_regex = make_consistent(canonicalize(guessed_regex), &self.examples);
+
+        if let Ok(regex) = edged_regex(&guessed_regex.to_string()) {
+            self.guessed_regex = Some(regex);
+        }
+    }
+
+    fn hypothesis(&self) -> Option<Regex> {
+        self.guessed_regex.clone()
+    }
+}
+
+//refitting usually settles down after a step or two, if it does at all
+const MAX_REPAIRS: usize = 8;
+
+/// The indices of the examples `regex` gets wrong.
+pub fn misclassified(regex: &Regex, examples: &[Example]) -> Vec<usize> {
+    examples
+        .iter()
+        .enumerate()
+        .filter(|&(_, e)| regex.is_match(&e.text) != e.matched)
+        .map(|(i, _)| i)
+        .collect()
+}
+
+/// Fixing one example can break another, so this keeps refitting `regex` to
+/// whichever example it gets wrong. If that goes around in circles it gives up
+/// and uses what RPNI makes of the examples instead, which always fits them,
+/// or if that's too big to write out, just lists the matching examples.
+pub fn make_consistent(mut regex: RegexAst, examples: &[Example]) -> RegexAst {
+    let mut tried = Vec::new();
+
+    while tried.len() < MAX_REPAIRS && !tried.contains(&regex) {
+        let dfa = Dfa::from_ast(&regex);
+
+        match examples.iter().find(|e| dfa.accepts(&e.text) != e.matched) {
+            Some(wrong) => {
+                let repaired = canonicalize(fit(&regex, wrong));
+                tried.push(regex);
+                regex = repaired;
+            }
+            None => return regex,
+        }
+    }
+
+    let (positives, negatives): (Vec<&Example>, Vec<&Example>) =
+        examples.iter().partition(|e| e.matched);
+    let texts = |examples: Vec<&Example>| {
+        examples.iter().map(|e| e.text.clone()).collect::<Vec<_>>()
+    };
+
+    dfa_to_regex(&rpni(&texts(positives), &texts(negatives)))
+        .unwrap_or_else(|| canonicalize(positive_alternatives(examples)))
+}
+
+fn positive_alternatives(examples: &[Example]) -> RegexAst {
+    RegexAst::from_alternatives(examples
+                                    .iter()
+                                    .filter(|ex| ex.matched)
+                                    .map(|ex| {
+                                             RegexAst::Group(Box::new(RegexAst::literal(&ex.text)))
+                                         })
+                                    .collect())
+}
+
+//stretches or shrinks the alternatives of `regex` so it gets `example` right
+fn fit(regex: &RegexAst, example: &Example) -> RegexAst {
+    let mut sub_regexes = regex.alternatives();
+
+    if example.matched {
+        //extend a regex to make the new example match
+        for s in sub_regexes.iter_mut() {
+            if let Some(extended) = extend_to_fit(s, example) {
+                *s = extended;
+                break;
+            }
+        }
+    } else {
+        //make sure none of the sub_regexes match the new example
+        for s in sub_regexes.iter_mut() {
+            if let Some(contracted) = contract_to_avoid(s, example) {
+                *s = contracted;
+            }
+        }
+    }
+
+    RegexAst::from_alternatives(sub_regexes)
+}
+
+fn extend_to_fit(regex: &RegexAst, example: &Example) -> Option<RegexAst> {
+    if Dfa::from_ast(regex).accepts(&example.text) {
+        None
+    } else {
+        //TODO handle more cases
+        let group = RegexAst::Group(Box::new(regex.clone()));
+        let mut try = RegexAst::Plus(Box::new(group.clone()));
+
+        if Dfa::from_ast(&try).accepts(&example.text) {
+            return Some(try);
+        }
+
+        try = RegexAst::Star(Box::new(group));
+
+        if Dfa::from_ast(&try).accepts(&example.text) {
+            return Some(try);
+        }
+
+        Some(RegexAst::Or(vec![regex.clone(), RegexAst::literal(&example.text)]))
+    }
+}
+fn contract_to_avoid(regex: &RegexAst, example: &Example) -> Option<RegexAst> {
+    if Dfa::from_ast(regex).accepts(&example.text) {
+        //TODO handle more cases
+        //try dropping a trailing `*` or `+`, starting from the last one.
+        let mut sequence = regex.sequence();
+
+        for i in (0..sequence.len()).rev() {
+            let unrepeated = match sequence[i] {
+                RegexAst::Star(ref inner) |
+                RegexAst::Plus(ref inner) => (**inner).clone(),
+                _ => continue,
+            };
+
+            let previous = std::mem::replace(&mut sequence[i], unrepeated);
+            let try = RegexAst::from_sequence(sequence.clone());
+
+            if !Dfa::from_ast(&try).accepts(&example.text) {
+                return Some(try);
+            }
+
+            sequence[i] = previous;
+        }
+
+        None
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod make_consistent {
+    use super::*;
+
+    fn labelled(examples: &[(&str, bool)]) -> Vec<Example> {
+        examples
+            .iter()
+            .map(|&(text, matched)| {
+                     Example {
+                         text: text.to_owned(),
+                         matched,
+                     }
+                 })
+            .collect()
+    }
+
+    fn is_consistent(regex: &RegexAst, examples: &[Example]) -> bool {
+        let regex = edged_regex(&regex.to_string()).unwrap();
+
+        misclassified(&regex, examples).is_empty()
+    }
+
+    #[test]
+    fn minimal() {
+        let examples = labelled(&[("0", true)]);
+
+        assert_eq!(RegexAst::Digit(0),
+                   make_consistent(RegexAst::Digit(0), &examples));
+    }
+    #[test]
+    fn repairs_old_negatives() {
+        let examples = labelled(&[("0", true), ("00", true), ("000", false)]);
+        let repaired = make_consistent(RegexAst::parse("0+").unwrap(), &examples);
+
+        assert!(is_consistent(&repaired, &examples));
+    }
+    #[test]
+    fn falls_back_to_the_positives() {
+        let examples = labelled(&[("0", true), ("1", false), ("22", true)]);
+        let repaired = make_consistent(RegexAst::Dot, &examples);
+
+        assert!(is_consistent(&repaired, &examples));
+    }
+}
+
+#[cfg(test)]
+mod heuristic_learner {
+    use super::*;
+    use unedged;
+
+    fn labelled(text: &str, matched: bool) -> Example {
+        Example {
+            text: text.to_owned(),
+            matched,
+        }
+    }
+
+    #[test]
+    fn minimal() {
+        assert!(HeuristicLearner::new().hypothesis().is_none());
+    }
+    #[test]
+    fn positives_become_alternatives() {
+        let mut learner = HeuristicLearner::new();
+        learner.observe(&labelled("0", true));
+        learner.observe(&labelled("1", false));
+
+        assert_eq!("0", unedged(learner.hypothesis().unwrap().as_str()));
+    }
+}
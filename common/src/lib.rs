@@ -2,11 +2,46 @@ extern crate rand;
 extern crate regex;
 
 use std::fmt;
+use std::time::Instant;
 
 use rand::{StdRng, Rand, Rng};
 
 use regex::Regex;
 
+mod automaton;
+mod benchmark;
+mod campaign;
+mod daily;
+mod enumerative;
+mod layout;
+mod learner;
+mod lstar;
+mod puzzle_code;
+mod regex_ast;
+mod rpni;
+mod score;
+mod simplify;
+mod teacher;
+mod text_field;
+pub use benchmark::{BenchmarkReport, run_benchmark};
+pub use campaign::{Level, LEVELS, PROGRESS_FILE, load_progress, save_progress};
+pub use daily::{DailyResult, RESULTS_FILE, today, date_string, daily_regex, load_results,
+                record_result};
+pub use learner::{Learner, LearnerKind, Question, HeuristicLearner, new_learner, misclassified,
+                  make_consistent};
+pub use layout::{Constraint, Anchor, screen, padded, row, column, anchored};
+pub use lstar::LStarLearner;
+pub use puzzle_code::{PuzzleCode, CodeError};
+pub use enumerative::{EnumerativeLearner, smallest_consistent};
+pub use rpni::{RpniLearner, rpni};
+pub use score::{Score, EXAMPLE_BUDGET, difficulty};
+pub use automaton::{Dfa, regexes_equivalent, shortest_counterexample, ALPHABET, ALPHABET_SIZE};
+pub use regex_ast::{RegexAst, ParseError};
+pub use text_field::TextField;
+pub use teacher::{Teacher, TeacherKind, characteristic_sample};
+pub use simplify::{simplify_regex, simplify, simplify_regex_once, sort_sub_regexes, canonicalize,
+                   dfa_to_regex};
+
 pub struct Platform {
     pub print_xy: fn(i32, i32, &str),
     pub clear: fn(Option<Rect>),
@@ -28,19 +63,78 @@ pub struct Platform {
 pub struct State {
     pub rng: StdRng,
     pub title_screen: bool,
-    pub text: String,
+    //where the master types in a regex of their own
+    pub setup_screen: bool,
+    //where a puzzle code can be typed in
+    pub code_screen: bool,
+    //where the campaign's levels can be picked from
+    pub campaign_screen: bool,
+    //the day, if this is the daily puzzle
+    pub daily: Option<u64>,
+    //the index into `LEVELS`, if this is a campaign puzzle
+    pub level: Option<usize>,
+    //the level picked out on the campaign screen
+    pub campaign_choice: usize,
+    //how many levels had been finished when the campaign screen was opened
+    pub levels_finished: usize,
+    pub mode: Mode,
+    pub text: TextField,
     pub regex: Regex,
-    pub guessed_regex: Regex,
+    pub guessed_regex: Option<Regex>,
+    pub disagreement: Option<String>,
     pub examples: Vec<Example>,
+    pub example_scroll: ScrollPosition,
     pub turn: Turn,
+    pub learner_kind: LearnerKind,
+    //NOTE(Ryan1729): the vtable for this lives in whichever copy of the code
+    //created the learner, so a hot reload while this is around may break it.
+    pub learner: Box<dyn Learner>,
+    //whether the game answers the learner's questions instead of the master
+    pub auto_answer: bool,
+    pub teacher: Teacher,
+    //when the spectator mode last showed an example, or `None` while it's paused
+    pub last_step: Option<Instant>,
+    //feedback on the last thing the player did, if it needs any
+    pub message: Option<String>,
     pub ui_context: UIContext,
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Mode {
+    //the player picks the regex and the computer tries to figure it out
+    Master,
+    //the computer picks the regex and the player tries to figure it out, like
+    //in the original Rezendo
+    Guesser,
+    //like `Guesser`, but a computer player sees the same examples and whoever
+    //guesses the regex first wins
+    Race,
+    //like `Master`, but the regex stays in the player's head and they label
+    //each example by hand
+    Labelling,
+    //like `Master`, but the computer has to figure it out within
+    //`EXAMPLE_BUDGET` examples, and it gets scored
+    Challenge,
+    //a computer teacher picks the examples and the player watches the
+    //computer learner try to keep up
+    Spectator,
+    //like `Guesser`, but the computer picks which examples to show
+    Taught,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum Turn {
     InProgress,
+    //waiting for the master to say whether the string is in the language
+    Query(String),
+    //waiting for the master to say whether the guess is right
+    Conjecture,
     Finished,
+    //the challenge's examples ran out before the guess was right
+    OutOfExamples,
 }
 
+#[derive(Clone, Debug)]
 pub struct Example {
     pub text: String,
     pub matched: bool,
@@ -57,12 +151,12 @@ impl Example {
     pub fn print_xy(&self, platform: &Platform, x: i32, y: i32) {
         let fg = (platform.get_foreground)();
 
+        self.print_mark_xy(platform, x, y);
+
         if self.matched {
             (platform.set_foreground)(MATCH_COLOUR);
-            (platform.print_xy)(x, y, "☑");
         } else {
             (platform.set_foreground)(NON_MATCH_COLOUR);
-            (platform.print_xy)(x, y, "☒");
         }
 
         if self.text.is_empty() {
@@ -73,6 +167,21 @@ impl Example {
 
         (platform.set_foreground)(fg);
     }
+
+    /// Just the ☑ or ☒, for when the text is shown some other way.
+    pub fn print_mark_xy(&self, platform: &Platform, x: i32, y: i32) {
+        let fg = (platform.get_foreground)();
+
+        if self.matched {
+            (platform.set_foreground)(MATCH_COLOUR);
+            (platform.print_xy)(x, y, "☑");
+        } else {
+            (platform.set_foreground)(NON_MATCH_COLOUR);
+            (platform.print_xy)(x, y, "☒");
+        }
+
+        (platform.set_foreground)(fg);
+    }
 }
 
 const NON_MATCH_COLOUR: Color = Color {
@@ -90,10 +199,47 @@ const MATCH_COLOUR: Color = Color {
 
 pub type UiId = i32;
 
+/// The id of the widget labelled `label` inside the one with the id `parent`,
+/// or at the top level if `parent` is 0. Ids made this way are never 0, which
+/// means no widget.
+pub fn child_id(parent: UiId, label: &str) -> UiId {
+    //32 bit FNV-1a, over the parent's id then the label
+    let mut hash: u32 = 0x811c_9dc5;
+
+    for &byte in parent.to_le_bytes().iter().chain(label.as_bytes()) {
+        hash ^= byte as u32;
+        hash = hash.wrapping_mul(0x0100_0193);
+    }
+
+    //keeping the top bit clear and the next one set keeps them positive,
+    //and well clear of 0
+    ((hash >> 2) | 0x4000_0000) as UiId
+}
+
+/// The id for the widget at the end of `path`, which is the labels of the
+/// widgets it's inside, and then its own.
+pub fn widget_id(path: &[&str]) -> UiId {
+    path.iter().fold(0, |id, label| child_id(id, label))
+}
+
 pub struct UIContext {
     pub hot: UiId,
     pub active: UiId,
     pub next_hot: UiId,
+    //whether a dialog was open last frame, so nothing else can be used
+    pub modal: bool,
+    next_modal: bool,
+    //whether the widgets being done now are part of the dialog
+    in_dialog: bool,
+    //every id used so far this frame
+    claimed: Vec<UiId>,
+    //the widget Tab and Shift-Tab have moved to, or 0
+    pub focused: UiId,
+    //whether Space or Enter was pressed for the focused widget this frame
+    pub focus_pressed: bool,
+    //the widgets that can be focused, in the order they were done. Input is
+    //gathered before the frame starts, so Tab goes by last frame's
+    focusable: Vec<UiId>,
 }
 
 impl UIContext {
@@ -102,6 +248,13 @@ impl UIContext {
             hot: 0,
             active: 0,
             next_hot: 0,
+            modal: false,
+            next_modal: false,
+            in_dialog: false,
+            claimed: Vec::new(),
+            focused: 0,
+            focus_pressed: false,
+            focusable: Vec::new(),
         }
     }
 
@@ -122,9 +275,195 @@ impl UIContext {
             self.hot = self.next_hot;
         }
         self.next_hot = 0;
+
+        self.modal = self.next_modal;
+        self.next_modal = false;
+        self.claimed.clear();
+        self.focusable.clear();
+    }
+
+    /// Notes that a widget is using `id` this frame. Two widgets with the
+    /// same id would both react to the mouse, so that's a bug.
+    pub fn claim(&mut self, id: UiId) {
+        debug_assert!(!self.claimed.contains(&id), "two widgets have the id {}", id);
+
+        self.claimed.push(id);
+    }
+
+    /// Whether the widget being done now can be used. While a dialog is
+    /// open, only the dialog's own widgets can be.
+    pub fn interactive(&self) -> bool {
+        !self.modal || self.in_dialog
+    }
+
+    /// Starts the widgets of a dialog, which keeps everything else from
+    /// being used next frame.
+    pub fn begin_dialog(&mut self) {
+        self.in_dialog = true;
+        self.next_modal = true;
+    }
+    pub fn end_dialog(&mut self) {
+        self.in_dialog = false;
+    }
+
+    /// Adds the widget with `id` to the ones Tab moves through, after the
+    /// ones added before it this frame.
+    pub fn add_focusable(&mut self, id: UiId) {
+        self.focusable.push(id);
+    }
+
+    /// Whether a widget that's still there has the focus.
+    pub fn has_focus(&self) -> bool {
+        self.focusable.contains(&self.focused)
+    }
+
+    /// Moves the focus to the next widget, or the first one if none of
+    /// them have it, wrapping around at the end.
+    pub fn focus_next(&mut self) {
+        let len = self.focusable.len();
+
+        if let Some(index) = self.focus_index() {
+            self.focused = self.focusable[(index + 1) % len];
+        } else if let Some(&first) = self.focusable.first() {
+            self.focused = first;
+        }
+    }
+
+    /// Moves the focus to the previous widget, or the last one if none of
+    /// them have it, wrapping around at the start.
+    pub fn focus_previous(&mut self) {
+        let len = self.focusable.len();
+
+        if let Some(index) = self.focus_index() {
+            self.focused = self.focusable[(index + len - 1) % len];
+        } else if let Some(&last) = self.focusable.last() {
+            self.focused = last;
+        }
+    }
+
+    fn focus_index(&self) -> Option<usize> {
+        self.focusable.iter().position(|&id| id == self.focused)
+    }
+}
+
+#[cfg(test)]
+mod widget_id {
+    use super::*;
+
+    #[test]
+    fn minimal() {
+        assert_eq!(widget_id(&["game", "Submit"]), widget_id(&["game", "Submit"]));
+        assert_ne!(widget_id(&["game", "Submit"]), widget_id(&["title", "Submit"]));
+    }
+    #[test]
+    fn path_not_just_text() {
+        assert_ne!(widget_id(&["ab", "c"]), widget_id(&["a", "bc"]));
+        assert_eq!(widget_id(&["dialog", "OK"]), child_id(widget_id(&["dialog"]), "OK"));
+    }
+    #[test]
+    fn never_nothing() {
+        for label in ["", "0", "1", "New Puzzle", "⌫"].iter() {
+            assert!(widget_id(&[label]) > 0);
+            assert!(child_id(0, label) > 0);
+        }
+    }
+    #[test]
+    fn no_collisions_between_similar_labels() {
+        let mut ids: Vec<UiId> = (0..10_000)
+            .map(|i| widget_id(&["keyboard", &i.to_string()]))
+            .collect();
+        ids.sort();
+        ids.dedup();
+
+        assert_eq!(10_000, ids.len());
+    }
+}
+
+#[cfg(test)]
+mod focus_next {
+    use super::*;
+
+    fn context_with(ids: &[UiId]) -> UIContext {
+        let mut context = UIContext::new();
+        for &id in ids.iter() {
+            context.add_focusable(id);
+        }
+        context
+    }
+
+    #[test]
+    fn minimal() {
+        let mut context = context_with(&[1, 2, 3]);
+
+        context.focus_next();
+        assert_eq!(1, context.focused);
+        context.focus_next();
+        assert_eq!(2, context.focused);
+    }
+    #[test]
+    fn wraps_around() {
+        let mut context = context_with(&[1, 2, 3]);
+
+        context.focus_previous();
+        assert_eq!(3, context.focused);
+        context.focus_next();
+        assert_eq!(1, context.focused);
+        context.focus_previous();
+        assert_eq!(3, context.focused);
+    }
+    #[test]
+    fn widget_went_away() {
+        let mut context = context_with(&[1, 2, 3]);
+        context.focused = 4;
+
+        assert!(!context.has_focus());
+        context.focus_next();
+        assert_eq!(1, context.focused);
+    }
+    #[test]
+    fn nothing_to_focus() {
+        let mut context = context_with(&[]);
+
+        context.focus_next();
+        context.focus_previous();
+        assert_eq!(0, context.focused);
+        assert!(!context.has_focus());
+    }
+}
+
+#[cfg(test)]
+mod claim {
+    use super::*;
+
+    #[test]
+    fn once_per_frame() {
+        let mut context = UIContext::new();
+
+        context.claim(1);
+        context.frame_init();
+        context.claim(1);
+    }
+    #[test]
+    #[should_panic]
+    #[cfg(debug_assertions)]
+    fn twice_in_one_frame() {
+        let mut context = UIContext::new();
+
+        context.claim(1);
+        context.claim(1);
     }
 }
 
+/// How far a list has been scrolled, kept between frames.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ScrollPosition {
+    //how many entries the newest one is below the bottom of the list, so `0`
+    //means it's showing
+    pub from_bottom: usize,
+    //how many entries there were last frame, so new ones can be scrolled to
+    pub len: usize,
+}
+
 pub enum Times {
     Once,
     ZeroOrMore,
@@ -173,58 +512,26 @@ impl Rand for RERule {
     }
 }
 
+//NOTE(Ryan1729): `^0|1$` means `(^0)|(1$)` to the regex crate, so the
+//whole expression needs to be wrapped for the edges to apply to every
+//alternative.
 pub fn edged_regex(s: &str) -> Result<Regex, regex::Error> {
-    //TODO could skip an allocation if already edged
-    let mut string = String::from(s);
-
-    if !string.starts_with('^') {
-        string.insert(0, '^');
-    }
-
-    if !string.ends_with('$') {
-        string.push('$');
-    }
-
-    Regex::new(&string)
-}
-
-pub fn sort_sub_regexes(regex: &str) -> String {
-    let mut sub_regexes = get_sub_regexes(regex);
+    let inner_regex = unedged(s);
 
-    for s in sub_regexes.iter_mut() {
-        *s = remove_parens(&s);
+    //the regex crate rejects empty groups
+    if inner_regex.is_empty() {
+        Regex::new("^$")
+    } else {
+        Regex::new(&format!("^(?:{})$", inner_regex))
     }
-
-    sub_regexes.sort();
-    sub_regexes.dedup();
-
-    collect_sub_regexes(sub_regexes)
 }
 
-pub fn collect_sub_regexes(sub_regexes: Vec<String>) -> String {
-    let mut result = String::new();
-
-    if sub_regexes.len() == 1 {
-        result.push_str(&sub_regexes[0]);
-        return result;
+//the inverse of `edged_regex`, also accepting plain `^...$` edges.
+pub fn unedged(regex: &str) -> &str {
+    if regex.starts_with("^(?:") && regex.ends_with(")$") {
+        return &regex[4..regex.len() - 2];
     }
 
-    let mut not_first = false;
-
-    for s in sub_regexes.iter() {
-        if not_first {
-            result.push('|');
-        } else {
-            not_first = true;
-        }
-
-        result.push_str(s);
-    }
-
-    result
-}
-
-pub fn get_sub_regexes(regex: &str) -> Vec<String> {
     let mut inner_regex = if regex.starts_with('^') {
         regex.split_at(1).1
     } else {
@@ -237,18 +544,93 @@ pub fn get_sub_regexes(regex: &str) -> Vec<String> {
         inner_regex
     };
 
-    //TODO should this only split by the outermost layer of "|"? (i.e. not the ones in groups)
-    inner_regex.split("|").map(String::from).collect()
+    inner_regex
+}
+
+#[cfg(test)]
+mod edged_regex {
+    use super::{edged_regex, unedged};
+    #[test]
+    fn minimal() {
+        assert_eq!("", unedged(edged_regex("").unwrap().as_str()));
+    }
+    #[test]
+    fn round_trip() {
+        assert_eq!("0|1", unedged(edged_regex("0|1").unwrap().as_str()));
+        assert_eq!("0|1", unedged(edged_regex("^0|1$").unwrap().as_str()));
+    }
+    #[test]
+    fn edges_apply_to_every_alternative() {
+        let regex = edged_regex("0|1").unwrap();
+
+        assert!(regex.is_match("0"));
+        assert!(!regex.is_match("01"));
+        assert!(!regex.is_match("21"));
+    }
+}
+
+/// Which parts of the regex syntax generated puzzles can use. Digits are
+/// always allowed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Grammar {
+    //how deeply `Or` and `Group` can nest, counting from 0
+    pub max_depth: u8,
+    pub classes: bool,
+    pub dot: bool,
+    pub or: bool,
+    pub groups: bool,
+}
+
+pub const FULL_GRAMMAR: Grammar = Grammar {
+    max_depth: 3,
+    classes: true,
+    dot: true,
+    or: true,
+    groups: true,
+};
+
+impl Grammar {
+    //picks between the allowed rules the same way `RERule`'s `Rand` does,
+    //so the full grammar generates the same puzzles from the same seed
+    fn rule(&self, rng: &mut StdRng, depth: u8) -> RERule {
+        let nested = depth <= self.max_depth;
+
+        let allowed = [true,
+                       nested && self.or,
+                       self.classes,
+                       nested && self.groups,
+                       self.dot];
+        //an `i32`, like the `Rand` impls use, since other integer types
+        //use up the random numbers differently
+        let count = allowed.iter().filter(|&&a| a).count() as i32;
+
+        let chosen = allowed
+            .iter()
+            .enumerate()
+            .filter(|&(_, &a)| a)
+            .map(|(i, _)| i)
+            .nth(rng.gen_range(0, count) as usize)
+            .unwrap_or(0);
+
+        match chosen {
+            0 => Digit(rng.gen::<Times>()),
+            1 => Or,
+            2 => Class(rng.gen::<Times>()),
+            3 => Group(rng.gen::<Times>()),
+            _ => Dot,
+        }
+    }
 }
 
 pub fn generate_regex(rng: &mut StdRng) -> Regex {
-    loop {
-        let mut generated = generate_regex_helper(rng, String::new(), 0, 3);
+    generate_regex_with(rng, &FULL_GRAMMAR)
+}
 
-        generated.insert(0, '^');
-        generated.push('$');
+pub fn generate_regex_with(rng: &mut StdRng, grammar: &Grammar) -> Regex {
+    loop {
+        let generated = generate_unsimplified_regex(rng, grammar);
 
-        let result = edged_regex(&simplify_regex(&sort_sub_regexes(&generated)));
+        let result = edged_regex(&simplify_regex(&generated));
 
         debug_assert!(result.is_ok(), "bad regex generation!");
 
@@ -258,16 +640,19 @@ pub fn generate_regex(rng: &mut StdRng) -> Regex {
     }
 }
 
-fn generate_regex_helper(rng: &mut StdRng, mut s: String, depth: u8, max: u8) -> String {
-    let rule = if depth <= max {
-        rng.gen::<RERule>()
-    } else {
-        match rng.gen_range(0, 3) {
-            0 => Digit(rng.gen::<Times>()),
-            1 => Class(rng.gen::<Times>()),
-            _ => Dot,
-        }
-    };
+/// The regex `generate_regex_with` starts from, before it's simplified. It
+/// matches the same strings for a fraction of the cost, so it will do
+/// whenever only the language matters.
+pub fn generate_unsimplified_regex(rng: &mut StdRng, grammar: &Grammar) -> String {
+    generate_regex_helper(rng, String::new(), 0, grammar)
+}
+
+fn generate_regex_helper(rng: &mut StdRng,
+                         mut s: String,
+                         depth: u8,
+                         grammar: &Grammar)
+                         -> String {
+    let rule = grammar.rule(rng, depth);
 
     match rule {
         Digit(t) => {
@@ -281,9 +666,9 @@ fn generate_regex_helper(rng: &mut StdRng, mut s: String, depth: u8, max: u8) ->
             s.push_str(t.as_str());
         }
         Or => {
-            s = generate_regex_helper(rng, s, depth + 1, max);
+            s = generate_regex_helper(rng, s, depth + 1, grammar);
             s.push('|');
-            s = generate_regex_helper(rng, s, depth + 1, max);
+            s = generate_regex_helper(rng, s, depth + 1, grammar);
         }
         Class(t) => {
             s.push('[');
@@ -316,14 +701,10 @@ fn generate_regex_helper(rng: &mut StdRng, mut s: String, depth: u8, max: u8) ->
                 otherwise => otherwise,
             };
 
-            let regex_str = generate_regex_helper(rng, String::new(), depth + 1, max);
-
-            let mut finalized_regex_str = String::from("^");
-            finalized_regex_str.push_str(&regex_str);
-            finalized_regex_str.push('$');
+            let regex_str = generate_regex_helper(rng, String::new(), depth + 1, grammar);
 
             //only generate groupings if they will matter
-            if Regex::new(&finalized_regex_str)
+            if edged_regex(&regex_str)
                    .map(|regex| !regex.is_match(""))
                    .unwrap_or(false) {
                 s.push('(');
@@ -343,551 +724,6 @@ fn generate_regex_helper(rng: &mut StdRng, mut s: String, depth: u8, max: u8) ->
     s
 }
 
-pub fn simplify_regex(regex: &str) -> String {
-    let mut last_result = String::from(regex);
-
-    loop {
-        let current_result = simplify_regex_once(&last_result);
-        if current_result == last_result {
-            return current_result;
-        } else {
-            last_result = current_result;
-        }
-    }
-}
-pub fn simplify_regex_once(regex: &str) -> String {
-    let mut result = remove_parens(&regex);
-
-    result = sort_sub_regexes(&result);
-
-    result = merge_into_classes(&result);
-
-    result = convert_star_to_plus(&result);
-
-    result = convert_empty_or_plus_to_star(&result);
-
-    result
-}
-
-#[cfg(test)]
-mod simplify_regex {
-    use super::simplify_regex;
-    #[test]
-    fn minimal() {
-        assert_eq!("", simplify_regex(""));
-    }
-    #[test]
-    fn one_digit() {
-        assert_eq!("0", simplify_regex("0"));
-        assert_eq!("1", simplify_regex("1"));
-        assert_eq!("2", simplify_regex("2"));
-        assert_eq!("3", simplify_regex("3"));
-    }
-    #[test]
-    fn one_digit_merge() {
-        assert_eq!("[01]", simplify_regex("0|1"));
-        assert_eq!("[12]", simplify_regex("1|2"));
-        assert_eq!("[23]", simplify_regex("2|3"));
-        assert_eq!("[03]", simplify_regex("3|0"));
-    }
-    #[test]
-    fn one_digit_plus() {
-        assert_eq!("0+", simplify_regex("0+"));
-        assert_eq!("1+", simplify_regex("1+"));
-        assert_eq!("2+", simplify_regex("2+"));
-        assert_eq!("3+", simplify_regex("3+"));
-    }
-    #[test]
-    fn or_empty() {
-        assert_eq!("|0", simplify_regex("|0"));
-        assert_eq!("|1", simplify_regex("1|"));
-    }
-    #[test]
-    fn found_example_1() {
-        assert_eq!(".|1+|[012]*", simplify_regex("(1)+|.|1+|[012]*"));
-
-    }
-}
-
-fn convert_empty_or_plus_to_star(regex: &str) -> String {
-    let mut sub_regexes = get_sub_regexes(regex);
-    if sub_regexes.iter().any(|s| s.is_empty()) {
-        let mut found_one = false;
-        for s in sub_regexes.iter_mut() {
-            let len = s.len();
-            if len > 0 && s.ends_with('+') && is_class(s.split_at(len - 1).0) {
-                s.pop();
-                s.push('*');
-                found_one = true;
-                break;
-            }
-
-        }
-
-        if found_one {
-            sub_regexes.retain(|s| !s.is_empty());
-        }
-
-        collect_sub_regexes(sub_regexes)
-    } else {
-        String::from(regex)
-    }
-}
-
-fn merge_into_classes(regex: &str) -> String {
-    let mut last_result = String::from(regex);
-
-    loop {
-        let current_result = merge_into_classes_once(&last_result);
-        if current_result == last_result {
-            return current_result;
-        } else {
-            last_result = current_result;
-        }
-    }
-}
-
-#[cfg(test)]
-mod merge_into_classes {
-    use super::merge_into_classes;
-    #[test]
-    fn minimal() {
-        assert_eq!("", merge_into_classes(""));
-    }
-    #[test]
-    fn one_digit() {
-        assert_eq!("0", merge_into_classes("0"));
-        assert_eq!("1", merge_into_classes("1"));
-        assert_eq!("2", merge_into_classes("2"));
-        assert_eq!("3", merge_into_classes("3"));
-    }
-    #[test]
-    fn one_digit_merge() {
-        assert_eq!("[01]", merge_into_classes("0|1"));
-        assert_eq!("[12]", merge_into_classes("1|2"));
-        assert_eq!("[23]", merge_into_classes("2|3"));
-        assert_eq!("[03]", merge_into_classes("3|0"));
-    }
-    #[test]
-    fn one_digit_plus() {
-        assert_eq!("0+", merge_into_classes("0+"));
-        assert_eq!("1+", merge_into_classes("1+"));
-        assert_eq!("2+", merge_into_classes("2+"));
-        assert_eq!("3+", merge_into_classes("3+"));
-    }
-}
-
-fn merge_into_classes_once(regex: &str) -> String {
-    let sub_regexes = get_sub_regexes(regex);
-
-    if sub_regexes.len() <= 1 {
-        return String::from(regex);
-    }
-
-    let mut windows = sub_regexes.windows(2).peekable();
-
-    let mut result: Vec<String> = Vec::new();
-
-    while let Some(w) = windows.next() {
-        let ref first = w[0];
-        let ref second = w[1];
-        if first == second {
-            result.push(first.to_owned());
-
-            windows.next();
-
-            continue;
-        }
-
-
-        {
-            let first_classes_and_chars = split_into_classes_and_chars(&first);
-            let second_classes_and_chars = split_into_classes_and_chars(&second);
-
-            if let Some(indices) = class_mismatch_indices(&first_classes_and_chars,
-                                                          &second_classes_and_chars) {
-                debug_assert!(first_classes_and_chars.len() == second_classes_and_chars.len());
-                let mut sub_regex = String::new();
-                for i in 0..first_classes_and_chars.len() {
-                    if indices.contains(&i) {
-                        sub_regex.push_str(&class_union(&first_classes_and_chars[i],
-                                                        &second_classes_and_chars[i]));
-                    } else {
-                        //they are assumed to match
-                        sub_regex.push_str(first_classes_and_chars[i]);
-                    }
-                }
-
-                result.push(sub_regex);
-
-                windows.next();
-
-                continue;
-            }
-        }
-
-        result.push(first.to_owned());
-
-        if windows.peek().is_none() {
-            result.push(second.to_owned());
-        }
-    }
-
-    collect_sub_regexes(result)
-
-}
-
-fn class_mismatch_indices(first_classes_and_chars: &Vec<&str>,
-                          second_classes_and_chars: &Vec<&str>)
-                          -> Option<Vec<usize>> {
-    if first_classes_and_chars.len() != second_classes_and_chars.len() {
-        return None;
-    }
-
-    let mut result = Vec::new();
-
-    for (i, (first, second)) in
-        first_classes_and_chars
-            .iter()
-            .zip(second_classes_and_chars.iter())
-            .enumerate() {
-        if is_class(first) && is_class(second) {
-            result.push(i);
-        } else if first != second {
-            return None;
-        }
-    }
-
-    Some(result)
-}
-
-fn is_digit(regex: &str) -> bool {
-    match regex {
-        "0" | "1" | "2" | "3" => true,
-        _ => false,
-    }
-}
-fn is_digit_char(c: char) -> bool {
-    match c {
-        '0' | '1' | '2' | '3' => true,
-        _ => false,
-    }
-}
-fn is_class(regex: &str) -> bool {
-    is_digit(regex) ||
-    {
-        regex.starts_with("[") && regex.ends_with("]") && regex.len() > 2 &&
-        get_middle(regex).chars().all(|c| is_digit_char(c))
-
-    }
-}
-
-fn get_middle(s: &str) -> &str {
-    let len = s.len();
-
-    if len > 2 {
-        unsafe { s.slice_unchecked(1, len - 1) }
-    } else {
-        s.split_at(0).0
-    }
-}
-
-fn class_union(r1: &str, r2: &str) -> String {
-    match (extract_class_digits(r1), extract_class_digits(r2)) {
-        (Some(d1), Some(d2)) => {
-            let mut chars: Vec<char> = d1.chars().chain(d2.chars()).collect();
-            chars.sort();
-            chars.dedup();
-
-            if chars.len() >= 4 {
-                String::from(".")
-            } else {
-                chars.insert(0, '[');
-                chars.push(']');
-
-                chars.into_iter().collect()
-            }
-        }
-        _ => format!("({}|{})", r1, r2),
-    }
-}
-
-//could make an enum since there are only 15 valid possibilities
-fn extract_class_digits(s: &str) -> Option<String> {
-    if is_class(s) {
-        if s.len() == 1 {
-            Some(String::from(s))
-        } else {
-            let result = get_middle(s);
-            if result.len() > 0 {
-                Some(String::from(result))
-            } else {
-                None
-            }
-        }
-    } else {
-        None
-    }
-}
-
-fn convert_star_to_plus(regex: &str) -> String {
-    let classes_and_chars = split_into_classes_and_chars(regex);
-
-    let mut result = String::new();
-
-    if classes_and_chars.len() >= 3 {
-        let mut windows = classes_and_chars.windows(3).peekable();
-
-        while let Some(w) = windows.next() {
-            if w[0] == w[1] && w[2].chars().nth(0) == Some('*') {
-                result.push_str(w[0]);
-                result.push('+');
-
-                windows.next();
-                windows.next();
-            } else {
-                result.push_str(w[0]);
-
-                if windows.peek().is_none() {
-                    result.push_str(w[1]);
-                    result.push_str(w[2]);
-                }
-            }
-        }
-    } else {
-        result.push_str(regex);
-    }
-
-    result
-}
-
-#[cfg(test)]
-mod convert_star_to_plus {
-    use super::convert_star_to_plus;
-    #[test]
-    fn minimal() {
-        assert_eq!("", convert_star_to_plus(""));
-    }
-    #[test]
-    fn one_digit() {
-        assert_eq!("0", convert_star_to_plus("0"));
-        assert_eq!("1", convert_star_to_plus("1"));
-        assert_eq!("2", convert_star_to_plus("2"));
-        assert_eq!("3", convert_star_to_plus("3"));
-    }
-    #[test]
-    fn one_digit_star_to_plus() {
-        assert_eq!("0+", convert_star_to_plus("00*"));
-        assert_eq!("1+", convert_star_to_plus("11*"));
-        assert_eq!("2+", convert_star_to_plus("22*"));
-        assert_eq!("3+", convert_star_to_plus("33*"));
-    }
-    #[test]
-    fn one_digit_plus_to_plus() {
-        assert_eq!("0+", convert_star_to_plus("0+"));
-        assert_eq!("1+", convert_star_to_plus("1+"));
-        assert_eq!("2+", convert_star_to_plus("2+"));
-        assert_eq!("3+", convert_star_to_plus("3+"));
-    }
-}
-
-fn split_into_classes_and_chars(regex: &str) -> Vec<&str> {
-    let mut spilt_indices = Vec::new();
-
-    let mut split = true;
-
-    for (i, c) in regex.chars().enumerate() {
-        if c == '[' {
-            split = false;
-        }
-
-        if c == ']' {
-            split = true;
-        }
-
-        if split {
-            spilt_indices.push(i);
-        }
-    }
-
-    let mut result = Vec::new();
-
-    let mut last_index = 0;
-
-    if spilt_indices.len() >= 2 {
-        for indices in spilt_indices.windows(2) {
-            result.push(unsafe { regex.slice_unchecked(indices[0], indices[1]) });
-
-            last_index = indices[1];
-        }
-    }
-
-    result.push(regex.split_at(last_index).1);
-
-    result
-}
-
-fn remove_parens(regex: &str) -> String {
-    let mut removal_indicies = Vec::new();
-
-    for (left, _) in regex.match_indices('(') {
-        if let Some(right) = get_matching_paren_index(regex, left) {
-            let right_plus_1 = regex.chars().nth(right + 1);
-            if right_plus_1.map(|c| c != '*' && c != '+').unwrap_or(true) {
-                removal_indicies.push(left);
-
-                removal_indicies.push(right);
-            } else {
-                let between_parens = if left + 1 < right {
-                    unsafe { regex.slice_unchecked(left + 1, right) }
-                } else {
-                    continue;
-                };
-
-                if is_class(between_parens) {
-                    removal_indicies.push(left);
-
-                    removal_indicies.push(right);
-
-                    continue;
-                };
-
-                let all_but_last_between_parens = if left + 1 < right - 1 {
-                    between_parens.split_at(between_parens.len() - 1).0
-                } else {
-                    continue;
-                };
-
-                if is_class(all_but_last_between_parens) {
-                    //where `c` is a character clsaa
-                    //(c+)* = c* = (c*)+ = (c*)*
-                    //(c+)+ = c+
-                    match (regex.chars().nth(right - 1), right_plus_1) {
-                        //if the front is a star, leave that one
-                        (Some('*'), Some('*')) |
-                        (Some('+'), Some('*')) => {
-                            removal_indicies.push(left);
-
-                            removal_indicies.push(right - 1);
-                            removal_indicies.push(right);
-                        }
-                        //if the front is a plus, leave whatever is behind
-                        (Some('*'), Some('+')) |
-                        (Some('+'), Some('+')) => {
-                            removal_indicies.push(left);
-
-                            removal_indicies.push(right);
-                            removal_indicies.push(right + 1);
-
-                        }
-                        _ => {}
-                    }
-                }
-
-            }
-        }
-    }
-
-    let mut result = String::from(regex);
-
-    removal_indicies.sort();
-
-    for i in removal_indicies.iter().rev() {
-        result.remove(*i);
-    }
-
-    result
-}
-
-#[cfg(test)]
-mod remove_parens {
-    use super::remove_parens;
-    #[test]
-    fn minimal() {
-        assert_eq!("", remove_parens(""));
-    }
-    #[test]
-    fn one_digit_no_parens() {
-        assert_eq!("0", remove_parens("0"));
-        assert_eq!("1", remove_parens("1"));
-        assert_eq!("2", remove_parens("2"));
-        assert_eq!("3", remove_parens("3"));
-    }
-    #[test]
-    fn one_digit() {
-        assert_eq!("0", remove_parens("(0)"));
-        assert_eq!("1", remove_parens("(1)"));
-        assert_eq!("2", remove_parens("(2)"));
-        assert_eq!("3", remove_parens("(3)"));
-    }
-    #[test]
-    fn one_digit_times() {
-        assert_eq!("0*", remove_parens("(0)*"));
-        assert_eq!("1+", remove_parens("(1)+"));
-    }
-    #[test]
-    fn one_digit_star_and_plus() {
-        assert_eq!("0*", remove_parens("(0*)*"));
-        assert_eq!("1*", remove_parens("(1*)+"));
-        assert_eq!("2*", remove_parens("(2+)*"));
-        assert_eq!("3+", remove_parens("(3+)+"));
-    }
-}
-
-fn get_matching_paren_index(s: &str, left_index: usize) -> Option<usize> {
-    let mut right_index = left_index;
-    let mut counter = 1;
-    for c in s.chars().skip(left_index + 1) {
-        right_index += 1;
-
-        if c == '(' {
-            counter += 1;
-        } else if c == ')' {
-            counter -= 1;
-        }
-
-        if counter <= 0 {
-            return Some(right_index);
-        }
-    }
-
-    None
-}
-
-#[cfg(test)]
-mod get_matching_paren_index {
-    use super::get_matching_paren_index;
-
-    #[test]
-    fn minimal_none() {
-        assert_eq!(None, get_matching_paren_index("", 0));
-        assert_eq!(None, get_matching_paren_index("(", 0));
-    }
-
-    #[test]
-    fn minimal_find() {
-        assert_eq!(Some(1), get_matching_paren_index("()", 0));
-    }
-
-    #[test]
-    fn inner() {
-        assert_eq!(Some(3), get_matching_paren_index("1(2)3", 1));
-    }
-
-    #[test]
-    fn nested() {
-        assert_eq!(Some(4), get_matching_paren_index("(1(2)3)", 2));
-        assert_eq!(Some(6), get_matching_paren_index("(1(2)3)", 0));
-    }
-}
-
-
-
-
-
-
-
-
 //NOTE(Ryan1729): if I import BearLibTerminal.rs into `state_manipulation` or a crate
 //`state_manipulation` depends on, like this one for example, then the
 //ffi to the C version of BearLibTerminal causes an error. I just want
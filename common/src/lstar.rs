@@ -0,0 +1,379 @@
+use std::collections::HashMap;
+
+use regex::Regex;
+
+use automaton::{Dfa, ALPHABET, ALPHABET_SIZE};
+use learner::{Learner, Question};
+use simplify::dfa_to_regex;
+use {edged_regex, Example};
+
+/// Angluin's L*, with counterexamples handled by adding their suffixes as
+/// experiments (Maler and Pnueli's variant). That keeps the rows of the
+/// access strings distinct, so the table is always consistent and only ever
+/// needs to be closed before a conjecture can be made. The suffixes are added
+/// shortest first, and only until the conjecture gets the counterexample
+/// right, since every experiment costs a question for every row.
+pub struct LStarLearner {
+    //access strings, the rows of the table
+    prefixes: Vec<String>,
+    //experiments, the columns of the table
+    suffixes: Vec<String>,
+    answers: HashMap<String, bool>,
+    conjecture: Option<Dfa>,
+    guessed_regex: Option<Regex>,
+    //set once a conjecture is too big to write out, after which there's
+    //nothing left to ask
+    gave_up: bool,
+}
+
+impl LStarLearner {
+    pub fn new() -> Self {
+        LStarLearner {
+            prefixes: vec![String::new()],
+            suffixes: vec![String::new()],
+            answers: HashMap::new(),
+            conjecture: None,
+            guessed_regex: None,
+            gave_up: false,
+        }
+    }
+
+    fn extensions(&self) -> Vec<String> {
+        let mut result = Vec::new();
+
+        for prefix in self.prefixes.iter() {
+            for &c in ALPHABET.iter() {
+                let mut extension = prefix.clone();
+                extension.push(c);
+
+                if !self.prefixes.contains(&extension) {
+                    result.push(extension);
+                }
+            }
+        }
+
+        result
+    }
+
+    fn row(&self, prefix: &str) -> Option<Vec<bool>> {
+        self.suffixes
+            .iter()
+            .map(|suffix| self.answers.get(&format!("{}{}", prefix, suffix)).cloned())
+            .collect()
+    }
+
+    fn first_unknown(&self) -> Option<String> {
+        let mut unknown: Vec<String> = self.prefixes
+            .iter()
+            .chain(self.extensions().iter())
+            .flat_map(|prefix| {
+                          self.suffixes
+                              .iter()
+                              .map(move |suffix| format!("{}{}", prefix, suffix))
+                      })
+            .filter(|text| !self.answers.contains_key(text))
+            .collect();
+
+        unknown.sort_by(|a, b| (a.len(), a).cmp(&(b.len(), b)));
+
+        unknown.into_iter().next()
+    }
+
+    //Adds the shortest suffix of `text` that isn't an experiment yet, and
+    //returns whether there was one. Going shortest first keeps the
+    //experiments suffix closed.
+    fn add_suffix(&mut self, text: &str) -> bool {
+        let suffix = text.char_indices()
+            .map(|(i, _)| text[i..].to_string())
+            .rev()
+            .find(|suffix| !self.suffixes.contains(suffix));
+
+        match suffix {
+            Some(suffix) => {
+                self.suffixes.push(suffix);
+                true
+            }
+            None => false,
+        }
+    }
+
+    //the shortlex first answer `dfa` disagrees with
+    fn first_disagreement(&self, dfa: &Dfa) -> Option<String> {
+        self.answers
+            .iter()
+            .filter(|&(text, &matched)| dfa.accepts(text) != matched)
+            .map(|(text, _)| text.clone())
+            .min_by(|a, b| (a.len(), a).cmp(&(b.len(), b)))
+    }
+
+    //the shortlex first string there isn't an answer for yet
+    fn first_unanswered(&self) -> String {
+        let mut length = 0;
+
+        loop {
+            let count = ALPHABET_SIZE.pow(length as u32);
+
+            for index in 0..count {
+                let mut text = String::with_capacity(length);
+                let mut rest = index;
+                for _ in 0..length {
+                    text.insert(0, ALPHABET[rest % ALPHABET_SIZE]);
+                    rest /= ALPHABET_SIZE;
+                }
+
+                if !self.answers.contains_key(&text) {
+                    return text;
+                }
+            }
+
+            length += 1;
+        }
+    }
+
+    fn build_conjecture(&self) -> Dfa {
+        let rows: Vec<Vec<bool>> = self.prefixes
+            .iter()
+            .map(|p| self.row(p).unwrap_or_default())
+            .collect();
+
+        let state_of = |text: &str| {
+            let row = self.row(text).unwrap_or_default();
+            rows.iter().position(|r| *r == row).unwrap_or(0)
+        };
+
+        let mut dfa = Dfa {
+            transitions: Vec::with_capacity(self.prefixes.len()),
+            accepting: Vec::with_capacity(self.prefixes.len()),
+        };
+
+        for (prefix, row) in self.prefixes.iter().zip(rows.iter()) {
+            let mut transitions = [0; ALPHABET_SIZE];
+            for (target, &c) in transitions.iter_mut().zip(ALPHABET.iter()) {
+                let mut extension = prefix.clone();
+                extension.push(c);
+
+                *target = state_of(&extension);
+            }
+
+            dfa.transitions.push(transitions);
+            //the first experiment is always the empty string
+            dfa.accepting.push(row[0]);
+        }
+
+        dfa
+    }
+}
+
+impl Default for LStarLearner {
+    fn default() -> Self {
+        LStarLearner::new()
+    }
+}
+
+impl Learner for LStarLearner {
+    fn observe(&mut self, example: &Example) {
+        self.answers.insert(example.text.clone(), example.matched);
+
+        //whether or not it was a counterexample, the next conjecture is
+        //checked against it along with every other answer
+        self.conjecture = None;
+    }
+
+    fn hypothesis(&self) -> Option<Regex> {
+        self.guessed_regex.clone()
+    }
+
+    fn question(&mut self) -> Option<Question> {
+        if self.gave_up {
+            return None;
+        }
+        if self.conjecture.is_some() {
+            return Some(Question::IsEquivalent);
+        }
+
+        loop {
+            if let Some(text) = self.first_unknown() {
+                return Some(Question::IsMember(text));
+            }
+
+            let rows: Vec<Vec<bool>> = self.prefixes
+                .iter()
+                .filter_map(|p| self.row(p))
+                .collect();
+
+            let unclosed = self.extensions()
+                .into_iter()
+                .find(|e| self.row(e).map(|row| !rows.contains(&row)).unwrap_or(false));
+
+            if let Some(extension) = unclosed {
+                self.prefixes.push(extension);
+                continue;
+            }
+
+            //Counterexamples, and anything else the conjecture gets wrong,
+            //get their suffixes added until it gets them right. Once all of
+            //them are experiments it has to, since it agrees with every entry
+            //in the table.
+            let conjecture = self.build_conjecture();
+
+            if let Some(text) = self.first_disagreement(&conjecture) {
+                if self.add_suffix(&text) {
+                    continue;
+                }
+            }
+
+            //The regexes here can't express the empty language, and it is
+            //never the answer anyway, so the conjecture has to wait until
+            //there is a positive example to work from, which the check above
+            //then makes it accept.
+            if conjecture.accepting.iter().all(|&a| !a) {
+                return Some(Question::IsMember(self.first_unanswered()));
+            }
+
+            break;
+        }
+
+        let dfa = self.build_conjecture();
+
+        match dfa_to_regex(&dfa).and_then(|ast| edged_regex(&ast.to_string()).ok()) {
+            Some(regex) => {
+                self.guessed_regex = Some(regex);
+                self.conjecture = Some(dfa);
+
+                Some(Question::IsEquivalent)
+            }
+            //Conjectures are never bigger than the hidden regex's minimal
+            //automaton, so this doesn't happen for generated puzzles, but a
+            //typed in regex can be too big for state elimination. Asking
+            //about a regex other than the conjecture would be pointless.
+            None => {
+                self.guessed_regex = None;
+                self.gave_up = true;
+
+                None
+            }
+        }
+    }
+
+    fn gave_up(&self) -> bool {
+        self.gave_up
+    }
+}
+
+#[cfg(test)]
+mod lstar_learner {
+    use super::*;
+    use std::collections::HashSet;
+    use automaton::shortest_counterexample;
+
+    //plays the master perfectly, returning how many questions were asked
+    fn teach(target: &str) -> (LStarLearner, usize) {
+        let regex = edged_regex(target).unwrap();
+        let mut learner = LStarLearner::new();
+        let mut questions = 0;
+
+        loop {
+            questions += 1;
+            assert!(questions < 10000, "{} never converged", target);
+
+            match learner.question() {
+                Some(Question::IsMember(text)) => {
+                    learner.observe(&Example::new(&text, &regex));
+                }
+                Some(Question::IsEquivalent) => {
+                    match shortest_counterexample(&regex, &learner.hypothesis().unwrap()) {
+                        Some(text) => learner.observe(&Example::new(&text, &regex)),
+                        None => return (learner, questions),
+                    }
+                }
+                None => panic!("L* should always have a question"),
+            }
+        }
+    }
+
+    #[test]
+    fn minimal() {
+        let (learner, _) = teach("0");
+
+        assert_eq!("^(?:0)$", learner.hypothesis().unwrap().as_str());
+    }
+    #[test]
+    fn gives_up_when_the_guess_is_too_big() {
+        //"the fourth to last symbol is 0" needs 16 states, which state
+        //elimination can't write out
+        let regex = edged_regex(".*0...").unwrap();
+        let mut learner = LStarLearner::new();
+
+        for _ in 0..10000 {
+            let text = match learner.question() {
+                Some(Question::IsMember(text)) => text,
+                Some(Question::IsEquivalent) => {
+                    shortest_counterexample(&regex, &learner.hypothesis().unwrap()).unwrap()
+                }
+                None => break,
+            };
+
+            learner.observe(&Example::new(&text, &regex));
+        }
+
+        assert!(learner.gave_up());
+        assert!(learner.hypothesis().is_none());
+    }
+    #[test]
+    fn answers_that_are_not_counterexamples() {
+        //the master first answers every conjecture with something it already
+        //gets right, which mustn't stop the learner from asking again
+        let regex = edged_regex("[01]*2").unwrap();
+        let mut learner = LStarLearner::new();
+        let mut given = HashSet::new();
+        let mut done = false;
+
+        for _ in 0..10000 {
+            let text = match learner.question() {
+                Some(Question::IsMember(text)) => text,
+                Some(Question::IsEquivalent) => {
+                    let guess = learner.hypothesis().unwrap();
+                    //every string of length 4, in order
+                    let agreeing = (0..256)
+                        .map(|n: usize| {
+                            (0..4).map(|i| ALPHABET[n >> (2 * i) & 3]).collect::<String>()
+                        })
+                        .find(|text| guess.is_match(text) == regex.is_match(text) &&
+                                     given.insert(text.clone()));
+
+                    match agreeing {
+                        Some(text) => text,
+                        None => {
+                            match shortest_counterexample(&regex, &guess) {
+                                Some(text) => text,
+                                None => {
+                                    done = true;
+                                    break;
+                                }
+                            }
+                        }
+                    }
+                }
+                None => panic!("L* should always have a question"),
+            };
+
+            learner.observe(&Example::new(&text, &regex));
+        }
+
+        assert!(done);
+    }
+    #[test]
+    fn needs_a_positive_example() {
+        teach("00");
+        teach("3333");
+    }
+    #[test]
+    fn found_example_from_readme() {
+        teach("1+|[012]*");
+    }
+    #[test]
+    fn groups() {
+        teach("(01)+2|3*");
+        teach("(0[12]*3)*");
+    }
+}
@@ -0,0 +1,331 @@
+use std::fmt;
+
+use rand::{StdRng, SeedableRng};
+use regex::Regex;
+
+use regex_ast::RegexAst;
+use {edged_regex, generate_regex};
+
+/// Everything needed to hand someone else the same puzzle. A puzzle can be
+/// given either as its regex or as the seed that generates it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PuzzleCode {
+    pub regex: Option<String>,
+    pub seed: Option<usize>,
+    //just the texts, since the regex says which ones match
+    pub examples: Vec<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CodeError {
+    pub message: &'static str,
+}
+
+impl fmt::Display for CodeError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "{}", self.message)
+    }
+}
+
+fn error(message: &'static str) -> CodeError {
+    CodeError { message }
+}
+
+const VERSION: u8 = 1;
+
+//The code is a string of nibbles. The digits are `0` to `3`, the rest of the
+//regex syntax follows, and the last few are markers.
+const SYMBOLS: [char; 13] = ['0', '1', '2', '3', '[', ']', '(', ')', '.', '|', '*', '+', '?'];
+const EXAMPLE_END: u8 = 13;
+const REGEX_END: u8 = 15;
+const PADDING: u8 = 15;
+
+const HAS_REGEX: u8 = 1;
+const HAS_SEED: u8 = 2;
+const HAS_EXAMPLES: u8 = 4;
+
+//the URL safe base 64 alphabet, so a code can go in a link like Rezendo's do
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+impl PuzzleCode {
+    /// Fails if the regex or the examples have anything in them that a code
+    /// can't hold, rather than quietly leaving it out.
+    pub fn encode(&self) -> Result<String, CodeError> {
+        let mut nibbles = vec![VERSION];
+
+        let mut flags = 0;
+        if self.regex.is_some() {
+            flags |= HAS_REGEX;
+        }
+        if self.seed.is_some() {
+            flags |= HAS_SEED;
+        }
+        if !self.examples.is_empty() {
+            flags |= HAS_EXAMPLES;
+        }
+        nibbles.push(flags);
+
+        if let Some(ref regex) = self.regex {
+            for c in regex.chars() {
+                let symbol = SYMBOLS
+                    .iter()
+                    .position(|&s| s == c)
+                    .ok_or_else(|| error("that regex has something a code can't hold"))?;
+
+                nibbles.push(symbol as u8);
+            }
+            nibbles.push(REGEX_END);
+        }
+
+        if let Some(seed) = self.seed {
+            let mut digits = Vec::new();
+            let mut rest = seed;
+            loop {
+                digits.push((rest & 0xF) as u8);
+                rest >>= 4;
+                if rest == 0 {
+                    break;
+                }
+            }
+            digits.reverse();
+
+            nibbles.push((digits.len() - 1) as u8);
+            nibbles.extend(digits);
+        }
+
+        for example in self.examples.iter() {
+            for c in example.chars() {
+                let digit = c.to_digit(4)
+                    .ok_or_else(|| error("only the digits 0 to 3 can be in an example"))?;
+
+                nibbles.push(digit as u8);
+            }
+            nibbles.push(EXAMPLE_END);
+        }
+
+        if nibbles.len() % 2 == 1 {
+            nibbles.push(PADDING);
+        }
+
+        let bytes: Vec<u8> = nibbles.chunks(2).map(|pair| (pair[0] << 4) | pair[1]).collect();
+
+        Ok(to_base64(&bytes))
+    }
+
+    pub fn decode(code: &str) -> Result<PuzzleCode, CodeError> {
+        let bytes = from_base64(code.trim())?;
+        let mut nibbles = bytes.iter().flat_map(|&b| vec![b >> 4, b & 0xF]).peekable();
+
+        if nibbles.next() != Some(VERSION) {
+            return Err(error("that code is from a different version of the game"));
+        }
+        let flags = nibbles.next().ok_or_else(|| error("that code is too short"))?;
+
+        let mut result = PuzzleCode {
+            regex: None,
+            seed: None,
+            examples: Vec::new(),
+        };
+
+        if flags & HAS_REGEX != 0 {
+            let mut regex = String::new();
+            loop {
+                match nibbles.next() {
+                    Some(REGEX_END) => break,
+                    Some(n) if (n as usize) < SYMBOLS.len() => regex.push(SYMBOLS[n as usize]),
+                    Some(_) => return Err(error("that code has a broken regex")),
+                    None => return Err(error("that code is too short")),
+                }
+            }
+
+            if RegexAst::parse(&regex).is_err() {
+                return Err(error("that code has a broken regex"));
+            }
+
+            result.regex = Some(regex);
+        }
+
+        if flags & HAS_SEED != 0 {
+            let count = nibbles.next().ok_or_else(|| error("that code is too short"))? as usize + 1;
+
+            let mut seed: usize = 0;
+            for _ in 0..count {
+                let digit = nibbles.next().ok_or_else(|| error("that code is too short"))?;
+                seed = seed.checked_mul(16)
+                    .ok_or_else(|| error("that code has a seed that is too big"))? +
+                       digit as usize;
+            }
+
+            result.seed = Some(seed);
+        }
+
+        if flags & HAS_EXAMPLES != 0 {
+            let mut example = String::new();
+            while let Some(&n) = nibbles.peek() {
+                match n {
+                    EXAMPLE_END => result.examples.push(std::mem::take(&mut example)),
+                    n if n < 4 => example.push((b'0' + n) as char),
+                    PADDING => break,
+                    _ => return Err(error("that code has a broken example")),
+                }
+                nibbles.next();
+            }
+
+            //every example ends with a marker, so this one was cut off
+            if !example.is_empty() {
+                return Err(error("that code is too short"));
+            }
+        }
+
+        //the padding only ever fills out the last byte
+        let rest: Vec<u8> = nibbles.collect();
+        if !rest.is_empty() && rest != [PADDING] {
+            return Err(error("that code has something extra at the end"));
+        }
+
+        Ok(result)
+    }
+
+    /// The regex the puzzle is about, generating it from the seed if that's
+    /// all there is.
+    pub fn target(&self) -> Result<Regex, CodeError> {
+        match (self.regex.as_ref(), self.seed) {
+            (Some(regex), _) => {
+                edged_regex(regex).map_err(|_| error("that code has a broken regex"))
+            }
+            (None, Some(seed)) => {
+                let seed_slice: &[_] = &[seed];
+                let mut rng: StdRng = SeedableRng::from_seed(seed_slice);
+
+                Ok(generate_regex(&mut rng))
+            }
+            (None, None) => Err(error("that code doesn't have a puzzle in it")),
+        }
+    }
+}
+
+fn to_base64(bytes: &[u8]) -> String {
+    let mut result = String::new();
+
+    for chunk in bytes.chunks(3) {
+        let mut buffer = [0u8; 3];
+        buffer[..chunk.len()].copy_from_slice(chunk);
+        let joined = (buffer[0] as u32) << 16 | (buffer[1] as u32) << 8 | buffer[2] as u32;
+
+        //no padding, the length says how many bytes the last chunk has
+        for i in 0..chunk.len() + 1 {
+            result.push(BASE64[(joined >> (18 - 6 * i) & 0x3F) as usize] as char);
+        }
+    }
+
+    result
+}
+
+fn from_base64(code: &str) -> Result<Vec<u8>, CodeError> {
+    let values = code.bytes()
+        .map(|b| BASE64.iter().position(|&c| c == b).map(|v| v as u32))
+        .collect::<Option<Vec<u32>>>()
+        .ok_or_else(|| error("that isn't a puzzle code"))?;
+
+    if values.len() % 4 == 1 {
+        return Err(error("that isn't a puzzle code"));
+    }
+
+    let mut result = Vec::new();
+
+    for chunk in values.chunks(4) {
+        let mut joined = 0;
+        for (i, &value) in chunk.iter().enumerate() {
+            joined |= value << (18 - 6 * i);
+        }
+
+        for i in 0..chunk.len() - 1 {
+            result.push((joined >> (16 - 8 * i)) as u8);
+        }
+    }
+
+    Ok(result)
+}
+
+#[cfg(test)]
+mod encode_and_decode {
+    use super::*;
+
+    fn round_trip(code: &PuzzleCode) -> PuzzleCode {
+        PuzzleCode::decode(&code.encode().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn minimal() {
+        let code = PuzzleCode {
+            regex: Some(String::new()),
+            seed: None,
+            examples: Vec::new(),
+        };
+
+        assert_eq!(code, round_trip(&code));
+    }
+    #[test]
+    fn found_example_from_readme() {
+        let code = PuzzleCode {
+            regex: Some("1+|[012]*".to_string()),
+            seed: None,
+            examples: vec!["".to_string(), "3".to_string(), "0123".to_string()],
+        };
+
+        assert_eq!(code, round_trip(&code));
+        assert!(code.encode().unwrap().len() < 16);
+    }
+    #[test]
+    fn seeds() {
+        for &seed in [0, 42, 1 << 20, usize::MAX].iter() {
+            let code = PuzzleCode {
+                regex: None,
+                seed: Some(seed),
+                examples: Vec::new(),
+            };
+
+            assert_eq!(code, round_trip(&code));
+        }
+    }
+    #[test]
+    fn unencodable() {
+        let regex = PuzzleCode {
+            regex: Some("0{2}".to_string()),
+            seed: None,
+            examples: Vec::new(),
+        };
+        let example = PuzzleCode {
+            regex: Some("0".to_string()),
+            seed: None,
+            examples: vec!["04".to_string()],
+        };
+
+        assert!(regex.encode().is_err());
+        assert!(example.encode().is_err());
+    }
+    #[test]
+    fn bad_codes() {
+        assert!(PuzzleCode::decode("").is_err());
+        assert!(PuzzleCode::decode("not a code!").is_err());
+        assert!(PuzzleCode::decode("E").is_err());
+        //version 2
+        assert!(PuzzleCode::decode("IA").is_err());
+    }
+    #[test]
+    fn truncated_and_extended_codes() {
+        let code = PuzzleCode {
+            regex: Some("1+|[012]*".to_string()),
+            seed: None,
+            examples: vec!["".to_string(), "3".to_string(), "0123".to_string()],
+        };
+        let bytes = from_base64(&code.encode().unwrap()).unwrap();
+
+        //the last byte is the end of "0123", so without it the example is cut off
+        let truncated = to_base64(&bytes[..bytes.len() - 1]);
+        let extended = to_base64(&[&bytes[..], &[0]].concat());
+
+        assert!(PuzzleCode::decode(&truncated).is_err());
+        assert!(PuzzleCode::decode(&extended).is_err());
+    }
+}
@@ -0,0 +1,395 @@
+use std::fmt;
+
+use unedged;
+
+/// A parsed puzzle regex. Digits are stored as `0..=3` rather than as chars.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum RegexAst {
+    Empty,
+    Digit(u8),
+    //sorted and deduplicated, with at least two digits
+    Class(Vec<u8>),
+    Dot,
+    Concat(Vec<RegexAst>),
+    Or(Vec<RegexAst>),
+    Star(Box<RegexAst>),
+    Plus(Box<RegexAst>),
+    Optional(Box<RegexAst>),
+    Group(Box<RegexAst>),
+}
+use RegexAst::*;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseError {
+    pub index: usize,
+    pub message: &'static str,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "{} at position {}", self.message, self.index)
+    }
+}
+
+impl RegexAst {
+    /// Parses a regex string, with or without `^`/`$` edges.
+    pub fn parse(regex: &str) -> Result<RegexAst, ParseError> {
+        let mut parser = Parser {
+            chars: unedged(regex).chars().collect(),
+            index: 0,
+        };
+
+        let result = parser.alternation()?;
+
+        match parser.peek() {
+            None => Ok(result),
+            Some(')') => Err(parser.error("unmatched `)`")),
+            Some(_) => Err(parser.error("unexpected character")),
+        }
+    }
+
+    /// A regex matching exactly `text`, which should only contain digits from `0` to `3`.
+    pub fn literal(text: &str) -> RegexAst {
+        RegexAst::from_sequence(text.bytes()
+                                    .filter(|b| b'0' <= *b && *b <= b'3')
+                                    .map(|b| Digit(b - b'0'))
+                                    .collect())
+    }
+
+    /// Builds a concatenation without wrapping single items or leaving nested
+    /// concatenations around.
+    pub fn from_sequence(items: Vec<RegexAst>) -> RegexAst {
+        let mut flattened = Vec::new();
+
+        for item in items {
+            match item {
+                Empty => {}
+                Concat(inner) => flattened.extend(inner),
+                otherwise => flattened.push(otherwise),
+            }
+        }
+
+        match flattened.len() {
+            0 => Empty,
+            1 => flattened.pop().unwrap(),
+            _ => Concat(flattened),
+        }
+    }
+
+    /// Builds an alternation without wrapping single alternatives. No
+    /// alternatives at all gives `Empty`, which is what the old string based
+    /// code produced in that case.
+    pub fn from_alternatives(mut alternatives: Vec<RegexAst>) -> RegexAst {
+        match alternatives.len() {
+            0 => Empty,
+            1 => alternatives.pop().unwrap(),
+            _ => Or(alternatives),
+        }
+    }
+
+    /// The top-level alternatives. Alternations nested inside groups are left intact.
+    pub fn alternatives(&self) -> Vec<RegexAst> {
+        match *self {
+            Or(ref alternatives) => alternatives.clone(),
+            ref otherwise => vec![otherwise.clone()],
+        }
+    }
+
+    /// The items of the top-level concatenation.
+    pub fn sequence(&self) -> Vec<RegexAst> {
+        match *self {
+            Empty => Vec::new(),
+            Concat(ref items) => items.clone(),
+            ref otherwise => vec![otherwise.clone()],
+        }
+    }
+
+    /// The digits a single character position can be, if this matches exactly one character.
+    pub fn char_set(&self) -> Option<Vec<u8>> {
+        match *self {
+            Digit(d) => Some(vec![d]),
+            Class(ref digits) => Some(digits.clone()),
+            Dot => Some(vec![0, 1, 2, 3]),
+            Group(ref inner) => inner.char_set(),
+            _ => None,
+        }
+    }
+
+    /// The single character regex for the given digits, using `Dot` when all are present.
+    pub fn from_char_set(mut digits: Vec<u8>) -> RegexAst {
+        digits.sort();
+        digits.dedup();
+
+        match digits.len() {
+            0 => Empty,
+            1 => Digit(digits[0]),
+            4 => Dot,
+            _ => Class(digits),
+        }
+    }
+
+    pub fn is_nullable(&self) -> bool {
+        match *self {
+            Empty | Star(_) | Optional(_) => true,
+            Digit(_) | Class(_) | Dot => false,
+            Concat(ref items) => items.iter().all(|i| i.is_nullable()),
+            Or(ref alternatives) => alternatives.iter().any(|a| a.is_nullable()),
+            Plus(ref inner) | Group(ref inner) => inner.is_nullable(),
+        }
+    }
+
+    fn precedence(&self) -> u8 {
+        match *self {
+            Or(_) => 0,
+            Empty | Concat(_) => 1,
+            Star(_) | Plus(_) | Optional(_) => 2,
+            Digit(_) | Class(_) | Dot | Group(_) => 3,
+        }
+    }
+
+    //parenthesizes anything that binds less tightly than `level`
+    fn write_at(&self, formatter: &mut fmt::Formatter, level: u8) -> fmt::Result {
+        if self.precedence() < level {
+            write!(formatter, "(")?;
+            self.write_at(formatter, 0)?;
+            return write!(formatter, ")");
+        }
+
+        match *self {
+            Empty => Ok(()),
+            Digit(d) => write!(formatter, "{}", d),
+            Class(ref digits) => {
+                write!(formatter, "[")?;
+                for d in digits.iter() {
+                    write!(formatter, "{}", d)?;
+                }
+                write!(formatter, "]")
+            }
+            Dot => write!(formatter, "."),
+            Concat(ref items) => {
+                for item in items.iter() {
+                    item.write_at(formatter, 2)?;
+                }
+                Ok(())
+            }
+            Or(ref alternatives) => {
+                for (i, alternative) in alternatives.iter().enumerate() {
+                    if i > 0 {
+                        write!(formatter, "|")?;
+                    }
+                    alternative.write_at(formatter, 1)?;
+                }
+                Ok(())
+            }
+            Star(ref inner) => {
+                inner.write_at(formatter, 3)?;
+                write!(formatter, "*")
+            }
+            Plus(ref inner) => {
+                inner.write_at(formatter, 3)?;
+                write!(formatter, "+")
+            }
+            Optional(ref inner) => {
+                inner.write_at(formatter, 3)?;
+                write!(formatter, "?")
+            }
+            Group(ref inner) => {
+                write!(formatter, "(")?;
+                inner.write_at(formatter, 0)?;
+                write!(formatter, ")")
+            }
+        }
+    }
+}
+
+impl fmt::Display for RegexAst {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        self.write_at(formatter, 0)
+    }
+}
+
+struct Parser {
+    chars: Vec<char>,
+    index: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.index).cloned()
+    }
+
+    fn error(&self, message: &'static str) -> ParseError {
+        ParseError {
+            index: self.index,
+            message,
+        }
+    }
+
+    fn alternation(&mut self) -> Result<RegexAst, ParseError> {
+        let mut alternatives = vec![self.concatenation()?];
+
+        while self.peek() == Some('|') {
+            self.index += 1;
+            alternatives.push(self.concatenation()?);
+        }
+
+        Ok(RegexAst::from_alternatives(alternatives))
+    }
+
+    fn concatenation(&mut self) -> Result<RegexAst, ParseError> {
+        let mut items = Vec::new();
+
+        while let Some(c) = self.peek() {
+            if c == '|' || c == ')' {
+                break;
+            }
+
+            items.push(self.repetition()?);
+        }
+
+        Ok(RegexAst::from_sequence(items))
+    }
+
+    fn repetition(&mut self) -> Result<RegexAst, ParseError> {
+        let mut result = self.atom()?;
+
+        while let Some(c) = self.peek() {
+            result = match c {
+                '*' => Star(Box::new(result)),
+                '+' => Plus(Box::new(result)),
+                '?' => Optional(Box::new(result)),
+                _ => break,
+            };
+            self.index += 1;
+        }
+
+        Ok(result)
+    }
+
+    fn atom(&mut self) -> Result<RegexAst, ParseError> {
+        let c = match self.peek() {
+            Some(c) => c,
+            None => return Err(self.error("unexpected end")),
+        };
+
+        match c {
+            '(' => {
+                self.index += 1;
+                let inner = self.alternation()?;
+                if self.peek() != Some(')') {
+                    return Err(self.error("unclosed `(`"));
+                }
+                self.index += 1;
+
+                Ok(Group(Box::new(inner)))
+            }
+            '[' => {
+                self.index += 1;
+                let mut digits = Vec::new();
+                loop {
+                    match self.peek() {
+                        Some(']') => break,
+                        Some(c @ '0'..='3') => digits.push(c as u8 - b'0'),
+                        Some(_) => {
+                            return Err(self.error("only the digits 0 to 3 can be in a class"))
+                        }
+                        None => return Err(self.error("unclosed `[`")),
+                    }
+                    self.index += 1;
+                }
+
+                if digits.is_empty() {
+                    return Err(self.error("empty class"));
+                }
+                self.index += 1;
+
+                Ok(RegexAst::from_char_set(digits))
+            }
+            '.' => {
+                self.index += 1;
+                Ok(Dot)
+            }
+            '0'..='3' => {
+                self.index += 1;
+                Ok(Digit(c as u8 - b'0'))
+            }
+            '*' | '+' | '?' => Err(self.error("nothing to repeat")),
+            _ => Err(self.error("only the digits 0 to 3 and `[]().|*+?` are supported")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod parse {
+    use super::*;
+
+    fn round_trip(regex: &str) -> String {
+        RegexAst::parse(regex).unwrap().to_string()
+    }
+
+    #[test]
+    fn minimal() {
+        assert_eq!(Ok(Empty), RegexAst::parse(""));
+        assert_eq!("", round_trip(""));
+    }
+    #[test]
+    fn one_digit() {
+        assert_eq!(Ok(Digit(0)), RegexAst::parse("0"));
+        assert_eq!(Ok(Digit(3)), RegexAst::parse("^3$"));
+    }
+    #[test]
+    fn classes() {
+        assert_eq!(Ok(Class(vec![0, 2])), RegexAst::parse("[20]"));
+        assert_eq!(Ok(Digit(1)), RegexAst::parse("[1]"));
+        assert_eq!(Ok(Dot), RegexAst::parse("[0123]"));
+    }
+    #[test]
+    fn round_trips() {
+        assert_eq!("0|1", round_trip("0|1"));
+        assert_eq!("|0", round_trip("|0"));
+        assert_eq!("(0|1)*2+", round_trip("(0|1)*2+"));
+        assert_eq!("(0*)*", round_trip("(0*)*"));
+        assert_eq!(".[12]?", round_trip(".[12]?"));
+    }
+    #[test]
+    fn errors() {
+        assert_eq!(Some(0), RegexAst::parse("*").err().map(|e| e.index));
+        assert_eq!(Some(2), RegexAst::parse("(0").err().map(|e| e.index));
+        assert_eq!(Some(1), RegexAst::parse("0)").err().map(|e| e.index));
+        assert_eq!(Some(2), RegexAst::parse("[04]").err().map(|e| e.index));
+        assert_eq!(Some(0), RegexAst::parse("a").err().map(|e| e.index));
+    }
+}
+
+#[cfg(test)]
+mod alternatives {
+    use super::*;
+
+    #[test]
+    fn minimal() {
+        assert_eq!(vec![Empty], RegexAst::parse("").unwrap().alternatives());
+    }
+    #[test]
+    fn nested_alternations_stay_together() {
+        let alternatives: Vec<String> = RegexAst::parse("0|(1|2)3")
+            .unwrap()
+            .alternatives()
+            .iter()
+            .map(|a| a.to_string())
+            .collect();
+
+        assert_eq!(vec!["0", "(1|2)3"], alternatives);
+    }
+}
+
+#[cfg(test)]
+mod display {
+    use super::*;
+
+    #[test]
+    fn adds_needed_parens() {
+        let ast = Star(Box::new(Concat(vec![Digit(0), Or(vec![Digit(1), Dot])])));
+
+        assert_eq!("(0(1|.))*", ast.to_string());
+    }
+}
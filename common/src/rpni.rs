@@ -0,0 +1,295 @@
+use regex::Regex;
+
+use automaton::{Dfa, ALPHABET, ALPHABET_SIZE};
+use learner::Learner;
+use simplify::dfa_to_regex;
+use {edged_regex, Example};
+
+/// Regular Positive and Negative Inference. Builds a prefix tree out of the
+/// positive examples, then merges states in shortlex order whenever the merge
+/// keeps every negative example rejected. Once the examples include a
+/// characteristic sample of the hidden language this finds it exactly.
+pub struct RpniLearner {
+    positives: Vec<String>,
+    negatives: Vec<String>,
+    guessed_regex: Option<Regex>,
+}
+
+impl RpniLearner {
+    pub fn new() -> Self {
+        RpniLearner {
+            positives: Vec::new(),
+            negatives: Vec::new(),
+            guessed_regex: None,
+        }
+    }
+}
+
+impl Default for RpniLearner {
+    fn default() -> Self {
+        RpniLearner::new()
+    }
+}
+
+impl Learner for RpniLearner {
+    fn observe(&mut self, example: &Example) {
+        if example.matched {
+            self.positives.push(example.text.clone());
+        } else {
+            self.negatives.push(example.text.clone());
+        }
+
+        let dfa = rpni(&self.positives, &self.negatives);
+
+        if let Some(regex) = dfa_to_regex(&dfa).and_then(|ast| edged_regex(&ast.to_string()).ok()) {
+            self.guessed_regex = Some(regex);
+        }
+    }
+
+    fn hypothesis(&self) -> Option<Regex> {
+        self.guessed_regex.clone()
+    }
+}
+
+pub fn rpni(positives: &[String], negatives: &[String]) -> Dfa {
+    let mut automaton = PartialDfa::prefix_tree(positives);
+
+    let mut red = vec![0];
+
+    while let Some(blue) = automaton.next_blue(&red) {
+        let merged = red.iter()
+            .filter_map(|&r| {
+                            let mut attempt = automaton.clone();
+                            attempt.merge(r, blue);
+
+                            if negatives.iter().any(|n| attempt.accepts(n)) {
+                                None
+                            } else {
+                                Some(attempt)
+                            }
+                        })
+            .next();
+
+        match merged {
+            Some(attempt) => automaton = attempt,
+            None => red.push(blue),
+        }
+    }
+
+    automaton.to_dfa()
+}
+
+#[derive(Clone)]
+struct PartialDfa {
+    transitions: Vec<[Option<usize>; ALPHABET_SIZE]>,
+    accepting: Vec<bool>,
+    //the shortlex least string reaching each state in the prefix tree
+    access: Vec<String>,
+}
+
+impl PartialDfa {
+    fn prefix_tree(positives: &[String]) -> Self {
+        let mut result = PartialDfa {
+            transitions: vec![[None; ALPHABET_SIZE]],
+            accepting: vec![false],
+            access: vec![String::new()],
+        };
+
+        for text in positives.iter() {
+            let symbols: Option<Vec<usize>> = text.chars()
+                .map(|c| ALPHABET.iter().position(|&a| a == c))
+                .collect();
+            //nothing outside the alphabet can match, so there's nothing to
+            //learn from a string with some in it
+            let symbols = match symbols {
+                Some(symbols) => symbols,
+                None => continue,
+            };
+
+            let mut state = 0;
+
+            for (c, symbol) in text.chars().zip(symbols) {
+                state = match result.transitions[state][symbol] {
+                    Some(next) => next,
+                    None => {
+                        let next = result.transitions.len();
+                        let mut access = result.access[state].clone();
+                        access.push(c);
+
+                        result.transitions.push([None; ALPHABET_SIZE]);
+                        result.accepting.push(false);
+                        result.access.push(access);
+                        result.transitions[state][symbol] = Some(next);
+
+                        next
+                    }
+                };
+            }
+
+            result.accepting[state] = true;
+        }
+
+        result
+    }
+
+    //the shortlex least state one step away from the red states
+    fn next_blue(&self, red: &[usize]) -> Option<usize> {
+        red.iter()
+            .flat_map(|&r| self.transitions[r].iter().filter_map(|&next| next))
+            .filter(|next| !red.contains(next))
+            .min_by(|&a, &b| {
+                        let (access_a, access_b) = (&self.access[a], &self.access[b]);
+                        (access_a.len(), access_a).cmp(&(access_b.len(), access_b))
+                    })
+    }
+
+    fn merge(&mut self, red: usize, blue: usize) {
+        for row in self.transitions.iter_mut() {
+            for next in row.iter_mut() {
+                if *next == Some(blue) {
+                    *next = Some(red);
+                }
+            }
+        }
+
+        self.fold(red, blue);
+    }
+
+    //`blue` is the root of a tree, so folding it in can't loop forever
+    fn fold(&mut self, red: usize, blue: usize) {
+        if self.accepting[blue] {
+            self.accepting[red] = true;
+        }
+
+        for symbol in 0..ALPHABET_SIZE {
+            if let Some(blue_next) = self.transitions[blue][symbol] {
+                match self.transitions[red][symbol] {
+                    Some(red_next) => self.fold(red_next, blue_next),
+                    None => self.transitions[red][symbol] = Some(blue_next),
+                }
+            }
+        }
+    }
+
+    fn accepts(&self, text: &str) -> bool {
+        let mut state = 0;
+
+        for c in text.chars() {
+            let next = ALPHABET
+                .iter()
+                .position(|&a| a == c)
+                .and_then(|symbol| self.transitions[state][symbol]);
+
+            match next {
+                Some(next) => state = next,
+                None => return false,
+            }
+        }
+
+        self.accepting[state]
+    }
+
+    //keeps only the states reachable from the start and adds a dead state
+    fn to_dfa(&self) -> Dfa {
+        let mut numbering = vec![None; self.transitions.len()];
+        let mut order = vec![0];
+        numbering[0] = Some(0);
+
+        let mut current = 0;
+        while current < order.len() {
+            for next in self.transitions[order[current]].iter().filter_map(|&n| n) {
+                if numbering[next].is_none() {
+                    numbering[next] = Some(order.len());
+                    order.push(next);
+                }
+            }
+            current += 1;
+        }
+
+        let dead = order.len();
+        let mut dfa = Dfa {
+            transitions: Vec::with_capacity(dead + 1),
+            accepting: Vec::with_capacity(dead + 1),
+        };
+
+        for &state in order.iter() {
+            let mut row = [dead; ALPHABET_SIZE];
+            for (target, next) in row.iter_mut().zip(self.transitions[state].iter()) {
+                if let Some(next) = *next {
+                    *target = numbering[next].unwrap();
+                }
+            }
+
+            dfa.transitions.push(row);
+            dfa.accepting.push(self.accepting[state]);
+        }
+
+        dfa.transitions.push([dead; ALPHABET_SIZE]);
+        dfa.accepting.push(false);
+
+        dfa
+    }
+}
+
+#[cfg(test)]
+mod state_merging {
+    use super::rpni;
+    use automaton::Dfa;
+
+    fn strings(texts: &[&str]) -> Vec<String> {
+        texts.iter().map(|t| t.to_string()).collect()
+    }
+
+    #[test]
+    fn minimal() {
+        let dfa = rpni(&strings(&[""]), &strings(&[]));
+
+        assert!(dfa.accepts(""));
+    }
+    #[test]
+    fn keeps_negatives_rejected() {
+        let positives = strings(&["0", "00", "1"]);
+        let negatives = strings(&["", "2", "01", "10"]);
+        let dfa = rpni(&positives, &negatives);
+
+        for p in positives.iter() {
+            assert!(dfa.accepts(p));
+        }
+        for n in negatives.iter() {
+            assert!(!dfa.accepts(n));
+        }
+    }
+    #[test]
+    fn found_example_from_readme() {
+        //a characteristic sample for `1+|[012]*`
+        let positives = strings(&["", "0", "1", "2", "00", "01", "02", "10", "11", "12",
+                                  "20", "21", "22"]);
+        let negatives = strings(&["3", "03", "13", "23", "30", "31", "32", "33"]);
+        let dfa = rpni(&positives, &negatives);
+
+        assert!(dfa.equivalent(&Dfa::from_regex_str("1+|[012]*").unwrap()));
+    }
+    #[test]
+    fn skips_strings_outside_the_alphabet() {
+        let dfa = rpni(&strings(&["1", "04"]), &strings(&["0"]));
+
+        assert!(dfa.accepts("1"));
+        assert!(!dfa.accepts("0"));
+    }
+}
+
+#[cfg(test)]
+mod rpni_learner {
+    use super::*;
+
+    #[test]
+    fn no_guess_from_negatives_alone() {
+        let mut learner = RpniLearner::new();
+        learner.observe(&Example {
+                             text: "0".to_string(),
+                             matched: false,
+                         });
+
+        assert!(learner.hypothesis().is_none());
+    }
+}
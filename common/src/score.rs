@@ -0,0 +1,83 @@
+use regex::Regex;
+
+use automaton::Dfa;
+
+/// How many examples the master gets in the challenge mode.
+pub const EXAMPLE_BUDGET: usize = 10;
+
+const SOLVED_POINTS: usize = 100;
+const POINTS_PER_SPARE_EXAMPLE: usize = 20;
+
+/// The number of states in the puzzle's minimal automaton, counting the one
+/// for strings that can never match. Each state is something else the
+/// computer player has to be shown, so it works as a rough difficulty.
+pub fn difficulty(regex: &Regex) -> usize {
+    Dfa::from_regex_str(regex.as_str())
+        .map(|dfa| dfa.minimized().len())
+        .unwrap_or(0)
+}
+
+/// What a finished challenge was worth, and why.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Score {
+    //examples left over when the computer player figured it out
+    pub spare_examples: usize,
+    pub difficulty: usize,
+}
+
+impl Score {
+    /// The score for getting the computer player to `regex` with
+    /// `examples_used` examples, or `None` if that went over the budget.
+    pub fn new(regex: &Regex, examples_used: usize) -> Option<Score> {
+        if examples_used > EXAMPLE_BUDGET {
+            return None;
+        }
+
+        Some(Score {
+                 spare_examples: EXAMPLE_BUDGET - examples_used,
+                 difficulty: difficulty(regex),
+             })
+    }
+
+    pub fn total(&self) -> usize {
+        (SOLVED_POINTS + self.spare_examples * POINTS_PER_SPARE_EXAMPLE) * self.difficulty
+    }
+
+    /// One line for each part of the score, ending with the total.
+    pub fn breakdown(&self) -> Vec<String> {
+        vec![format!("{:<18}{:>6}", "figured out", SOLVED_POINTS),
+             format!("{:<18}{:>6}",
+                     format!("{} spare examples", self.spare_examples),
+                     self.spare_examples * POINTS_PER_SPARE_EXAMPLE),
+             format!("{:<18}{:>6}", "difficulty", format!("x{}", self.difficulty)),
+             format!("{:<18}{:>6}", "total", self.total())]
+    }
+}
+
+#[cfg(test)]
+mod total {
+    use super::*;
+    use edged_regex;
+
+    fn score(regex: &str, examples_used: usize) -> Option<usize> {
+        Score::new(&edged_regex(regex).unwrap(), examples_used).map(|s| s.total())
+    }
+
+    #[test]
+    fn minimal() {
+        //`.` needs a start state, an accepting one and one for everything longer
+        assert_eq!(Some((100 + 9 * 20) * 3), score(".", 1));
+    }
+    #[test]
+    fn fewer_examples_score_more() {
+        assert!(score("1+|[012]*", 3) > score("1+|[012]*", 4));
+    }
+    #[test]
+    fn harder_puzzles_score_more() {
+        assert!(score("(01)+|2", 4) > score("[012]*", 4));
+    }
+    #[test]
+    fn over_budget() {
+        assert_eq!(None, score("0", EXAMPLE_BUDGET + 1));
+    }
+}
@@ -0,0 +1,759 @@
+use automaton::Dfa;
+use regex_ast::RegexAst;
+use regex_ast::RegexAst::*;
+
+pub fn simplify_regex(regex: &str) -> String {
+    match RegexAst::parse(regex) {
+        Ok(ast) => canonicalize(ast).to_string(),
+        Err(_) => String::from(regex),
+    }
+}
+
+//elimination orders are only searched exhaustively up to this many states
+const MAX_PERMUTED_STATES: usize = 5;
+//how many of the shortest elimination results get the rewrite search
+const SEARCHED_CANDIDATES: usize = 3;
+//every rewrite step checks every shorter rewrite, so results longer than this
+//take far too long to search and get left as they are
+const MAX_SEARCHED_LENGTH: usize = 200;
+
+/// A short form of `regex` built only from its minimal automaton, so every
+/// regex with the same language comes out the same. The minimal automaton is
+/// turned back into regexes by state elimination, and then rewrites that keep
+/// the language and shorten the result are applied until none are left.
+pub fn canonicalize(regex: RegexAst) -> RegexAst {
+    match dfa_to_regex(&Dfa::from_ast(&regex)) {
+        Some(result) => result,
+        //there's no way to write the empty language, and some automata are
+        //too big to write out
+        None => simplify(regex),
+    }
+}
+
+/// The same canonical form `canonicalize` gives, straight from an automaton.
+/// Returns `None` if the automaton accepts nothing, or if state elimination
+/// gives up on it.
+pub fn dfa_to_regex(dfa: &Dfa) -> Option<RegexAst> {
+    let dfa = dfa.minimized();
+
+    let mut candidates: Vec<(usize, String, RegexAst)> = elimination_orders(&dfa)
+        .iter()
+        .filter_map(|order| dfa.to_regex_ast(order))
+        .map(|candidate| keyed(tidy(candidate)))
+        .collect();
+
+    candidates.sort_by(|a, b| (a.0, &a.1).cmp(&(b.0, &b.1)));
+    candidates.dedup_by(|a, b| a.1 == b.1);
+    candidates.truncate(SEARCHED_CANDIDATES);
+
+    candidates
+        .into_iter()
+        .map(|candidate| if candidate.0 <= MAX_SEARCHED_LENGTH {
+                 search_rewrites(candidate, &dfa)
+             } else {
+                 candidate
+             })
+        .min_by(|a, b| (a.0, &a.1).cmp(&(b.0, &b.1)))
+        .map(|(_, _, ast)| ast)
+}
+
+fn keyed(regex: RegexAst) -> (usize, String, RegexAst) {
+    let printed = regex.to_string();
+
+    (printed.chars().count(), printed, regex)
+}
+
+fn tidy(regex: RegexAst) -> RegexAst {
+    simplify(convert_empty_or_other_to_optional(simplify(regex)))
+}
+
+fn elimination_orders(dfa: &Dfa) -> Vec<Vec<usize>> {
+    let live = dfa.live_states();
+    let states: Vec<usize> = (0..dfa.len()).filter(|&s| live[s]).collect();
+
+    if states.len() <= MAX_PERMUTED_STATES {
+        return permutations(&states);
+    }
+
+    //eliminating states with few connections first tends to give shorter regexes
+    let connections = |state: usize| {
+        let outgoing = dfa.transitions[state].iter().filter(|&&n| live[n]).count();
+        let incoming = dfa.transitions
+            .iter()
+            .flat_map(|row| row.iter())
+            .filter(|&&n| n == state)
+            .count();
+
+        incoming * outgoing
+    };
+
+    let mut fewest_connections_first = states.clone();
+    fewest_connections_first.sort_by_key(|&s| connections(s));
+
+    let mut reversed = states.clone();
+    reversed.reverse();
+
+    vec![states, reversed, fewest_connections_first]
+}
+
+fn permutations(items: &[usize]) -> Vec<Vec<usize>> {
+    if items.is_empty() {
+        return vec![Vec::new()];
+    }
+
+    let mut result = Vec::new();
+
+    for (i, &item) in items.iter().enumerate() {
+        let mut rest = items.to_vec();
+        rest.remove(i);
+
+        for mut permutation in permutations(&rest) {
+            permutation.insert(0, item);
+            result.push(permutation);
+        }
+    }
+
+    result
+}
+
+fn search_rewrites(start: (usize, String, RegexAst), target: &Dfa) -> (usize, String, RegexAst) {
+    let mut current = start;
+
+    loop {
+        let mut shorter: Vec<(usize, String, RegexAst)> = rewrites(&current.2)
+            .into_iter()
+            .map(|rewritten| keyed(tidy(rewritten)))
+            .filter(|k| (k.0, &k.1) < (current.0, &current.1))
+            .collect();
+
+        shorter.sort_by(|a, b| (a.0, &a.1).cmp(&(b.0, &b.1)));
+        shorter.dedup_by(|a, b| a.1 == b.1);
+
+        match shorter
+                  .into_iter()
+                  .find(|k| Dfa::from_ast(&k.2).equivalent(target)) {
+            Some(next) => current = next,
+            None => return current,
+        }
+    }
+}
+
+//Every regex one small edit away from `regex`. Most of them will match a
+//different language, so they need to be checked before being used.
+fn rewrites(regex: &RegexAst) -> Vec<RegexAst> {
+    let mut result = Vec::new();
+
+    match *regex {
+        Empty | Dot => {}
+        Digit(_) | Class(_) => result.push(Dot),
+        Concat(ref items) => {
+            for i in 0..items.len() {
+                let mut fewer = items.clone();
+                fewer.remove(i);
+                result.push(RegexAst::from_sequence(fewer));
+
+                for rewritten in rewrites(&items[i]) {
+                    let mut changed = items.clone();
+                    changed[i] = rewritten;
+                    result.push(RegexAst::from_sequence(changed));
+                }
+            }
+
+            //`1*0[01]*` to `[01]+`
+            for i in 0..items.len() {
+                let mut digits = Vec::new();
+
+                for j in i..items.len() {
+                    match repeated_char_set(&items[j]) {
+                        Some(more) => digits.extend(more),
+                        None => break,
+                    }
+
+                    if j > i {
+                        let repeated = Box::new(RegexAst::from_char_set(digits.clone()));
+
+                        for merged in [Star(repeated.clone()), Plus(repeated)].iter() {
+                            let mut changed = items[..i].to_vec();
+                            changed.push(merged.clone());
+                            changed.extend(items[j + 1..].iter().cloned());
+                            result.push(RegexAst::from_sequence(changed));
+                        }
+                    }
+                }
+            }
+        }
+        Or(ref alternatives) => {
+            for i in 0..alternatives.len() {
+                let mut fewer = alternatives.clone();
+                fewer.remove(i);
+                result.push(RegexAst::from_alternatives(fewer));
+
+                for rewritten in rewrites(&alternatives[i]) {
+                    let mut changed = alternatives.clone();
+                    changed[i] = rewritten;
+                    result.push(RegexAst::from_alternatives(changed));
+                }
+
+                for j in (i + 1)..alternatives.len() {
+                    for factored in factor(&alternatives[i], &alternatives[j]) {
+                        let mut changed = alternatives.clone();
+                        changed.remove(j);
+                        changed[i] = factored;
+                        result.push(RegexAst::from_alternatives(changed));
+                    }
+                }
+            }
+        }
+        Star(ref inner) => {
+            result.push((**inner).clone());
+            result.extend(rewrites(inner).into_iter().map(|r| Star(Box::new(r))));
+        }
+        Plus(ref inner) => {
+            result.push((**inner).clone());
+            result.push(Star(inner.clone()));
+            result.extend(rewrites(inner).into_iter().map(|r| Plus(Box::new(r))));
+        }
+        Optional(ref inner) => {
+            result.push((**inner).clone());
+            result.extend(rewrites(inner).into_iter().map(|r| Optional(Box::new(r))));
+        }
+        Group(ref inner) => {
+            result.extend(rewrites(inner).into_iter().map(|r| Group(Box::new(r))));
+        }
+    }
+
+    result
+}
+
+fn repeated_char_set(regex: &RegexAst) -> Option<Vec<u8>> {
+    match *regex {
+        Star(ref inner) | Plus(ref inner) | Optional(ref inner) => inner.char_set(),
+        ref otherwise => otherwise.char_set(),
+    }
+}
+
+//`ab|ac` to `a(b|c)` and `ac|bc` to `(a|b)c`
+fn factor(first: &RegexAst, second: &RegexAst) -> Vec<RegexAst> {
+    let first_sequence = first.sequence();
+    let second_sequence = second.sequence();
+    let shortest = ::std::cmp::min(first_sequence.len(), second_sequence.len());
+
+    let mut result = Vec::new();
+
+    let prefix_length = (0..shortest)
+        .take_while(|&i| first_sequence[i] == second_sequence[i])
+        .count();
+    if prefix_length > 0 {
+        let mut factored = first_sequence[..prefix_length].to_vec();
+        factored.push(Or(vec![RegexAst::from_sequence(first_sequence[prefix_length..].to_vec()),
+                              RegexAst::from_sequence(second_sequence[prefix_length..].to_vec())]));
+        result.push(RegexAst::from_sequence(factored));
+    }
+
+    let suffix_length = (0..shortest)
+        .take_while(|&i| {
+                        first_sequence[first_sequence.len() - 1 - i] ==
+                        second_sequence[second_sequence.len() - 1 - i]
+                    })
+        .count();
+    if suffix_length > 0 {
+        let first_rest = first_sequence[..first_sequence.len() - suffix_length].to_vec();
+        let second_rest = second_sequence[..second_sequence.len() - suffix_length].to_vec();
+
+        let mut factored = vec![Or(vec![RegexAst::from_sequence(first_rest),
+                                        RegexAst::from_sequence(second_rest)])];
+        factored.extend(first_sequence[first_sequence.len() - suffix_length..]
+                            .iter()
+                            .cloned());
+        result.push(RegexAst::from_sequence(factored));
+    }
+
+    result
+}
+
+//The regex crate rejects empty alternatives, so `|0` needs to be `0?`.
+fn convert_empty_or_other_to_optional(regex: RegexAst) -> RegexAst {
+    transform(regex, &|ast| match ast {
+        Or(mut alternatives) => {
+            if alternatives.contains(&Empty) {
+                alternatives.retain(|a| *a != Empty);
+
+                if !alternatives.iter().any(|a| a.is_nullable()) {
+                    return Optional(Box::new(RegexAst::from_alternatives(alternatives)));
+                }
+            }
+
+            RegexAst::from_alternatives(alternatives)
+        }
+        otherwise => otherwise,
+    })
+}
+
+pub fn simplify(regex: RegexAst) -> RegexAst {
+    let mut last_result = regex;
+
+    loop {
+        let current_result = simplify_regex_once(last_result.clone());
+        if current_result == last_result {
+            return current_result;
+        } else {
+            last_result = current_result;
+        }
+    }
+}
+pub fn simplify_regex_once(regex: RegexAst) -> RegexAst {
+    let mut result = remove_parens(regex);
+
+    result = sort_sub_regexes(result);
+
+    result = merge_into_classes(result);
+
+    result = convert_star_to_plus(result);
+
+    result = convert_empty_or_plus_to_star(result);
+
+    result
+}
+
+//the passes below take and return strings in their tests to keep them readable
+#[cfg(test)]
+fn on_str(pass: fn(RegexAst) -> RegexAst, regex: &str) -> String {
+    pass(RegexAst::parse(regex).unwrap()).to_string()
+}
+
+#[cfg(test)]
+mod simplify_regex {
+    use super::simplify_regex;
+    #[test]
+    fn minimal() {
+        assert_eq!("", simplify_regex(""));
+    }
+    #[test]
+    fn one_digit() {
+        assert_eq!("0", simplify_regex("0"));
+        assert_eq!("1", simplify_regex("1"));
+        assert_eq!("2", simplify_regex("2"));
+        assert_eq!("3", simplify_regex("3"));
+    }
+    #[test]
+    fn one_digit_merge() {
+        assert_eq!("[01]", simplify_regex("0|1"));
+        assert_eq!("[12]", simplify_regex("1|2"));
+        assert_eq!("[23]", simplify_regex("2|3"));
+        assert_eq!("[03]", simplify_regex("3|0"));
+    }
+    #[test]
+    fn one_digit_plus() {
+        assert_eq!("0+", simplify_regex("0+"));
+        assert_eq!("1+", simplify_regex("1+"));
+        assert_eq!("2+", simplify_regex("2+"));
+        assert_eq!("3+", simplify_regex("3+"));
+    }
+    #[test]
+    fn or_empty() {
+        assert_eq!("0?", simplify_regex("|0"));
+        assert_eq!("1?", simplify_regex("1|"));
+    }
+    #[test]
+    fn found_example_1() {
+        assert_eq!(".|[012]*", simplify_regex("(1)+|.|1+|[012]*"));
+
+    }
+    #[test]
+    fn nested_alternation() {
+        assert_eq!("[01]2", simplify_regex("(1|0)2"));
+        assert_eq!("03|123", simplify_regex("(12|0)3"));
+    }
+    #[test]
+    fn one_normal_form() {
+        assert_eq!(simplify_regex("[012]*|3"), simplify_regex("(1)+|.|1+|[012]*"));
+        assert_eq!(simplify_regex("0+"), simplify_regex("0*0"));
+        assert_eq!(simplify_regex("(0|1)*"), simplify_regex("(0*1*)*"));
+    }
+}
+
+#[cfg(test)]
+mod dfa_to_regex {
+    use super::*;
+
+    #[test]
+    fn long_results_skip_the_rewrite_search() {
+        //"the third to last symbol is 0" has 8 states, and state elimination
+        //writes it out in over 600 characters. Searching rewrites of that
+        //takes minutes.
+        let dfa = Dfa::from_regex_str(".*0..").unwrap();
+
+        let result = dfa_to_regex(&dfa).unwrap();
+
+        assert!(result.to_string().len() > MAX_SEARCHED_LENGTH);
+        assert!(Dfa::from_ast(&result).equivalent(&dfa));
+    }
+}
+
+//applies `f` to every node, children first
+fn transform<F>(regex: RegexAst, f: &F) -> RegexAst
+    where F: Fn(RegexAst) -> RegexAst
+{
+    let transformed = match regex {
+        Concat(items) => Concat(items.into_iter().map(|i| transform(i, f)).collect()),
+        Or(alternatives) => Or(alternatives.into_iter().map(|a| transform(a, f)).collect()),
+        Star(inner) => Star(Box::new(transform(*inner, f))),
+        Plus(inner) => Plus(Box::new(transform(*inner, f))),
+        Optional(inner) => Optional(Box::new(transform(*inner, f))),
+        Group(inner) => Group(Box::new(transform(*inner, f))),
+        leaf => leaf,
+    };
+
+    f(transformed)
+}
+
+fn convert_empty_or_plus_to_star(regex: RegexAst) -> RegexAst {
+    transform(regex, &|ast| match ast {
+        Or(mut alternatives) => {
+            if alternatives.contains(&Empty) {
+                let mut found_one = false;
+                for a in alternatives.iter_mut() {
+                    let converted = match *a {
+                        Plus(ref inner) => Star(inner.clone()),
+                        _ => continue,
+                    };
+
+                    *a = converted;
+                    found_one = true;
+                    break;
+                }
+
+                if found_one {
+                    alternatives.retain(|a| *a != Empty);
+                }
+            }
+
+            RegexAst::from_alternatives(alternatives)
+        }
+        otherwise => otherwise,
+    })
+}
+
+fn merge_into_classes(regex: RegexAst) -> RegexAst {
+    let mut last_result = regex;
+
+    loop {
+        let current_result = merge_into_classes_once(last_result.clone());
+        if current_result == last_result {
+            return current_result;
+        } else {
+            last_result = current_result;
+        }
+    }
+}
+
+#[cfg(test)]
+mod merge_into_classes {
+    fn merge_into_classes(regex: &str) -> String {
+        super::on_str(super::merge_into_classes, regex)
+    }
+    #[test]
+    fn minimal() {
+        assert_eq!("", merge_into_classes(""));
+    }
+    #[test]
+    fn one_digit() {
+        assert_eq!("0", merge_into_classes("0"));
+        assert_eq!("1", merge_into_classes("1"));
+        assert_eq!("2", merge_into_classes("2"));
+        assert_eq!("3", merge_into_classes("3"));
+    }
+    #[test]
+    fn one_digit_merge() {
+        assert_eq!("[01]", merge_into_classes("0|1"));
+        assert_eq!("[12]", merge_into_classes("1|2"));
+        assert_eq!("[23]", merge_into_classes("2|3"));
+        assert_eq!("[03]", merge_into_classes("3|0"));
+    }
+    #[test]
+    fn one_digit_plus() {
+        assert_eq!("0+", merge_into_classes("0+"));
+        assert_eq!("1+", merge_into_classes("1+"));
+        assert_eq!("2+", merge_into_classes("2+"));
+        assert_eq!("3+", merge_into_classes("3+"));
+    }
+    #[test]
+    fn only_one_position_differs() {
+        assert_eq!("0[12]", merge_into_classes("01|02"));
+        assert_eq!("01|23", merge_into_classes("01|23"));
+        assert_eq!("0*|1*", merge_into_classes("0*|1*"));
+    }
+}
+
+fn merge_into_classes_once(regex: RegexAst) -> RegexAst {
+    transform(regex, &|ast| match ast {
+        Or(mut alternatives) => {
+            'search: for i in 0..alternatives.len() {
+                for j in (i + 1)..alternatives.len() {
+                    if let Some(merged) = merge_pair(&alternatives[i], &alternatives[j]) {
+                        alternatives[i] = merged;
+                        alternatives.remove(j);
+                        break 'search;
+                    }
+                }
+            }
+
+            RegexAst::from_alternatives(alternatives)
+        }
+        otherwise => otherwise,
+    })
+}
+
+//two sequences can only be merged if they are the same except for one
+//single character position, otherwise the union would match extra strings.
+fn merge_pair(first: &RegexAst, second: &RegexAst) -> Option<RegexAst> {
+    if first == second {
+        return Some(first.clone());
+    }
+
+    let first_sequence = first.sequence();
+    let second_sequence = second.sequence();
+
+    if first_sequence.len() != second_sequence.len() {
+        return None;
+    }
+
+    let mismatch_indices: Vec<usize> = (0..first_sequence.len())
+        .filter(|&i| first_sequence[i] != second_sequence[i])
+        .collect();
+
+    if mismatch_indices.len() != 1 {
+        return None;
+    }
+
+    let index = mismatch_indices[0];
+
+    match (first_sequence[index].char_set(), second_sequence[index].char_set()) {
+        (Some(mut d1), Some(d2)) => {
+            d1.extend(d2);
+
+            let mut merged = first_sequence;
+            merged[index] = RegexAst::from_char_set(d1);
+
+            Some(RegexAst::from_sequence(merged))
+        }
+        _ => None,
+    }
+}
+
+fn convert_star_to_plus(regex: RegexAst) -> RegexAst {
+    transform(regex, &|ast| match ast {
+        Concat(items) => {
+            let mut result: Vec<RegexAst> = Vec::new();
+
+            for item in items {
+                let converted = match (result.last(), &item) {
+                    (Some(previous), Star(inner)) if **inner == *previous => {
+                        Some(Plus(inner.clone()))
+                    }
+                    _ => None,
+                };
+
+                match converted {
+                    Some(plus) => {
+                        result.pop();
+                        result.push(plus);
+                    }
+                    None => result.push(item),
+                }
+            }
+
+            RegexAst::from_sequence(result)
+        }
+        otherwise => otherwise,
+    })
+}
+
+#[cfg(test)]
+mod convert_star_to_plus {
+    fn convert_star_to_plus(regex: &str) -> String {
+        super::on_str(super::convert_star_to_plus, regex)
+    }
+    #[test]
+    fn minimal() {
+        assert_eq!("", convert_star_to_plus(""));
+    }
+    #[test]
+    fn one_digit() {
+        assert_eq!("0", convert_star_to_plus("0"));
+        assert_eq!("1", convert_star_to_plus("1"));
+        assert_eq!("2", convert_star_to_plus("2"));
+        assert_eq!("3", convert_star_to_plus("3"));
+    }
+    #[test]
+    fn one_digit_star_to_plus() {
+        assert_eq!("0+", convert_star_to_plus("00*"));
+        assert_eq!("1+", convert_star_to_plus("11*"));
+        assert_eq!("2+", convert_star_to_plus("22*"));
+        assert_eq!("3+", convert_star_to_plus("33*"));
+    }
+    #[test]
+    fn one_digit_plus_to_plus() {
+        assert_eq!("0+", convert_star_to_plus("0+"));
+        assert_eq!("1+", convert_star_to_plus("1+"));
+        assert_eq!("2+", convert_star_to_plus("2+"));
+        assert_eq!("3+", convert_star_to_plus("3+"));
+    }
+    #[test]
+    fn groups() {
+        assert_eq!("(01)+", convert_star_to_plus("(01)(01)*"));
+        assert_eq!("0(01)*", convert_star_to_plus("0(01)*"));
+    }
+}
+
+fn remove_parens(regex: RegexAst) -> RegexAst {
+    let mut result = transform(regex, &|ast| match ast {
+        Concat(items) => {
+            RegexAst::from_sequence(items
+                                        .into_iter()
+                                        .map(|item| match item {
+                                                 Group(inner) => {
+                                                     match *inner {
+                                                         Or(_) => Group(inner),
+                                                         otherwise => otherwise,
+                                                     }
+                                                 }
+                                                 otherwise => otherwise,
+                                             })
+                                        .collect())
+        }
+        Or(alternatives) => {
+            let mut flattened = Vec::new();
+
+            for alternative in alternatives {
+                match ungroup(alternative) {
+                    Or(inner) => flattened.extend(inner),
+                    otherwise => flattened.push(otherwise),
+                }
+            }
+
+            RegexAst::from_alternatives(flattened)
+        }
+        Star(inner) => {
+            match ungroup_repeated(*inner) {
+                //where `r` is any regex
+                //(r+)* = r* = (r*)+ = (r*)*
+                Star(r) | Plus(r) => Star(r),
+                otherwise => Star(Box::new(otherwise)),
+            }
+        }
+        Plus(inner) => {
+            match ungroup_repeated(*inner) {
+                //(r+)+ = r+
+                Star(r) => Star(r),
+                Plus(r) => Plus(r),
+                otherwise => Plus(Box::new(otherwise)),
+            }
+        }
+        Optional(inner) => Optional(Box::new(ungroup_repeated(*inner))),
+        Group(inner) => {
+            match *inner {
+                Group(r) => Group(r),
+                otherwise => Group(Box::new(otherwise)),
+            }
+        }
+        otherwise => otherwise,
+    });
+
+    while let Group(inner) = result {
+        result = *inner;
+    }
+
+    result
+}
+
+fn ungroup(regex: RegexAst) -> RegexAst {
+    match regex {
+        Group(inner) => ungroup(*inner),
+        otherwise => otherwise,
+    }
+}
+
+//only single characters and other repetitions can be repeated without a group
+fn ungroup_repeated(regex: RegexAst) -> RegexAst {
+    match regex {
+        Group(inner) => {
+            match ungroup(*inner) {
+                r @ Digit(_) | r @ Class(_) | r @ Dot | r @ Star(_) | r @ Plus(_) => r,
+                otherwise => Group(Box::new(otherwise)),
+            }
+        }
+        otherwise => otherwise,
+    }
+}
+
+#[cfg(test)]
+mod remove_parens {
+    fn remove_parens(regex: &str) -> String {
+        super::on_str(super::remove_parens, regex)
+    }
+    #[test]
+    fn minimal() {
+        assert_eq!("", remove_parens(""));
+    }
+    #[test]
+    fn one_digit_no_parens() {
+        assert_eq!("0", remove_parens("0"));
+        assert_eq!("1", remove_parens("1"));
+        assert_eq!("2", remove_parens("2"));
+        assert_eq!("3", remove_parens("3"));
+    }
+    #[test]
+    fn one_digit() {
+        assert_eq!("0", remove_parens("(0)"));
+        assert_eq!("1", remove_parens("(1)"));
+        assert_eq!("2", remove_parens("(2)"));
+        assert_eq!("3", remove_parens("(3)"));
+    }
+    #[test]
+    fn one_digit_times() {
+        assert_eq!("0*", remove_parens("(0)*"));
+        assert_eq!("1+", remove_parens("(1)+"));
+    }
+    #[test]
+    fn one_digit_star_and_plus() {
+        assert_eq!("0*", remove_parens("(0*)*"));
+        assert_eq!("1*", remove_parens("(1*)+"));
+        assert_eq!("2*", remove_parens("(2+)*"));
+        assert_eq!("3+", remove_parens("(3+)+"));
+    }
+    #[test]
+    fn needed_parens_stay() {
+        assert_eq!("0(1|2)", remove_parens("0(1|2)"));
+        assert_eq!("(01)*", remove_parens("((01))*"));
+        assert_eq!("0|1|2", remove_parens("0|(1|(2))"));
+    }
+}
+
+pub fn sort_sub_regexes(regex: RegexAst) -> RegexAst {
+    transform(remove_parens(regex), &|ast| match ast {
+        Or(mut alternatives) => {
+            alternatives.sort_by_key(|a| a.to_string());
+            alternatives.dedup();
+
+            RegexAst::from_alternatives(alternatives)
+        }
+        otherwise => otherwise,
+    })
+}
+
+#[cfg(test)]
+mod sort_sub_regexes {
+    fn sort_sub_regexes(regex: &str) -> String {
+        super::on_str(super::sort_sub_regexes, regex)
+    }
+    #[test]
+    fn minimal() {
+        assert_eq!("", sort_sub_regexes(""));
+    }
+    #[test]
+    fn sorts_and_dedups() {
+        assert_eq!("0|1", sort_sub_regexes("1|0|(1)"));
+        assert_eq!("(0|1)2|3", sort_sub_regexes("3|(1|0)2"));
+    }
+}
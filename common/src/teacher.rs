@@ -0,0 +1,449 @@
+use std::collections::{HashMap, VecDeque};
+
+use rand::{StdRng, SeedableRng};
+use regex::Regex;
+
+use automaton::{Dfa, ALPHABET, ALPHABET_SIZE};
+use {generate_unsimplified_regex, Example, FULL_GRAMMAR};
+
+//how many generated puzzles stand in for everything the regex could be
+const HYPOTHESIS_COUNT: usize = 300;
+//the longest string the splitting teacher considers showing
+const MAX_SPLIT_LENGTH: usize = 4;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TeacherKind {
+    //shows the shortest string the guess gets wrong, like the benchmark does
+    Counterexample,
+    //works through a sample that RPNI is guaranteed to learn the regex from,
+    //then falls back to counterexamples
+    Characteristic,
+    //shows whichever string splits the generated puzzles that fit the
+    //examples so far most evenly, then falls back to counterexamples
+    Splitting,
+}
+
+impl TeacherKind {
+    pub fn name(&self) -> &'static str {
+        match *self {
+            TeacherKind::Counterexample => "Mistakes",
+            TeacherKind::Characteristic => "Characteristic",
+            TeacherKind::Splitting => "Splitting",
+        }
+    }
+
+    pub fn next(&self) -> TeacherKind {
+        match *self {
+            TeacherKind::Counterexample => TeacherKind::Characteristic,
+            TeacherKind::Characteristic => TeacherKind::Splitting,
+            TeacherKind::Splitting => TeacherKind::Counterexample,
+        }
+    }
+}
+
+/// A computer master, picking which examples to show a learner.
+pub struct Teacher {
+    pub kind: TeacherKind,
+    //the examples still to be shown, worked out the first time they're needed
+    plan: Option<VecDeque<String>>,
+    //the minimal automata of the distinct generated puzzles, likewise
+    hypotheses: Option<Vec<Dfa>>,
+}
+
+impl Teacher {
+    pub fn new(kind: TeacherKind) -> Self {
+        Teacher {
+            kind,
+            plan: None,
+            hypotheses: None,
+        }
+    }
+
+    /// The next string to show for `target`, or `None` if the guess is
+    /// already right. The first one always matches, since the guesses can't
+    /// say that nothing does. No guess at all counts as matching nothing.
+    pub fn next_example(&mut self,
+                        target: &Regex,
+                        examples: &[Example],
+                        guess: Option<&Regex>)
+                        -> Option<String> {
+        if examples.is_empty() {
+            return Dfa::from_regex_str(target.as_str())
+                .and_then(|dfa| shortest_accepted(&dfa, 0));
+        }
+
+        if self.kind == TeacherKind::Characteristic {
+            let plan = self.plan
+                .get_or_insert_with(|| {
+                                        Dfa::from_regex_str(target.as_str())
+                                            .map(|dfa| characteristic_sample(&dfa.minimized()))
+                                            .unwrap_or_default()
+                                            .into_iter()
+                                            .collect()
+                                    });
+
+            while let Some(text) = plan.pop_front() {
+                if examples.iter().all(|e| e.text != text) {
+                    return Some(text);
+                }
+            }
+        }
+
+        if self.kind == TeacherKind::Splitting {
+            let hypotheses = self.hypotheses.get_or_insert_with(generated_hypotheses);
+
+            if let Some(text) = most_even_split(hypotheses, examples) {
+                return Some(text);
+            }
+        }
+
+        let target = Dfa::from_regex_str(target.as_str())?;
+        let guess = match guess {
+            Some(guess) => Dfa::from_regex_str(guess.as_str())?,
+            None => nothing(),
+        };
+
+        shortest_unshown_disagreement(&target, &guess, examples)
+    }
+}
+
+fn generated_hypotheses() -> Vec<Dfa> {
+    let mut result: Vec<Dfa> = Vec::new();
+
+    for seed in 0..HYPOTHESIS_COUNT {
+        let seed_slice: &[_] = &[seed];
+        let mut rng: StdRng = SeedableRng::from_seed(seed_slice);
+
+        //the simplified regex would be the same language, it's just slower
+        //to make, and this runs while the game is waiting
+        let generated = generate_unsimplified_regex(&mut rng, &FULL_GRAMMAR);
+
+        if let Some(dfa) = Dfa::from_regex_str(&generated) {
+            let dfa = dfa.minimized();
+
+            //minimal automata for the same language are numbered the same way
+            if result
+                   .iter()
+                   .all(|h| h.transitions != dfa.transitions || h.accepting != dfa.accepting) {
+                result.push(dfa);
+            }
+        }
+    }
+
+    result
+}
+
+/// The shortlex first string, out of the ones no longer than
+/// `MAX_SPLIT_LENGTH` that haven't been shown yet, that splits the hypotheses
+/// that fit every example as evenly as possible between the ones that match
+/// it and the ones that don't. Returns `None` if no string splits them at all.
+fn most_even_split(hypotheses: &[Dfa], examples: &[Example]) -> Option<String> {
+    let plausible: Vec<&Dfa> = hypotheses
+        .iter()
+        .filter(|h| examples.iter().all(|e| h.accepts(&e.text) == e.matched))
+        .collect();
+
+    let mut best: Option<(usize, String)> = None;
+    let mut texts = vec![String::new()];
+
+    for _ in 0..MAX_SPLIT_LENGTH + 1 {
+        for text in texts.iter() {
+            if examples.iter().any(|e| e.text == *text) {
+                continue;
+            }
+
+            let matching = plausible.iter().filter(|h| h.accepts(text)).count();
+            let split = matching.min(plausible.len() - matching);
+
+            if split > best.as_ref().map(|&(b, _)| b).unwrap_or(0) {
+                best = Some((split, text.clone()));
+            }
+        }
+
+        texts = texts
+            .iter()
+            .flat_map(|text| {
+                          ALPHABET.iter().map(move |&c| {
+                                                  let mut longer = text.clone();
+                                                  longer.push(c);
+                                                  longer
+                                              })
+                      })
+            .collect();
+    }
+
+    best.map(|(_, text)| text)
+}
+
+/// The shortlex first string `target` and `guess` disagree on that isn't
+/// one of the `examples`. The same search as `Dfa::shortest_disagreement`,
+/// but it also follows the string down a tree of the examples.
+fn shortest_unshown_disagreement(target: &Dfa,
+                                 guess: &Dfa,
+                                 examples: &[Example])
+                                 -> Option<String> {
+    let mut children: Vec<[Option<usize>; ALPHABET_SIZE]> = vec![[None; ALPHABET_SIZE]];
+    let mut shown = vec![false];
+
+    for example in examples.iter() {
+        let symbols: Option<Vec<usize>> = example.text
+            .chars()
+            .map(|c| ALPHABET.iter().position(|&a| a == c))
+            .collect();
+
+        //nothing outside the alphabet is a disagreement anyway
+        let symbols = match symbols {
+            Some(symbols) => symbols,
+            None => continue,
+        };
+
+        let mut node = 0;
+
+        for symbol in symbols {
+            node = match children[node][symbol] {
+                Some(child) => child,
+                None => {
+                    children.push([None; ALPHABET_SIZE]);
+                    shown.push(false);
+                    children[node][symbol] = Some(children.len() - 1);
+
+                    children.len() - 1
+                }
+            };
+        }
+
+        shown[node] = true;
+    }
+
+    //(target state, guess state, where the string is in the tree, if anywhere)
+    type Position = (usize, usize, Option<usize>);
+
+    let start = (0, 0, Some(0));
+    let mut parents: HashMap<Position, (Position, usize)> = HashMap::new();
+    let mut queue = VecDeque::new();
+    queue.push_back(start);
+
+    while let Some(position) = queue.pop_front() {
+        let (p, q, node) = position;
+
+        if target.accepting[p] != guess.accepting[q] && node.map(|n| shown[n]) != Some(true) {
+            let mut result = Vec::new();
+            let mut current = position;
+
+            while let Some(&(parent, symbol)) = parents.get(&current) {
+                result.push(ALPHABET[symbol]);
+                current = parent;
+            }
+
+            return Some(result.into_iter().rev().collect());
+        }
+
+        //once the string has left the tree it stays out
+        let branches = node.map(|n| children[n]).unwrap_or([None; ALPHABET_SIZE]);
+
+        for (symbol, &branch) in branches.iter().enumerate() {
+            let next = (target.transitions[p][symbol], guess.transitions[q][symbol], branch);
+
+            if next != start && !parents.contains_key(&next) {
+                parents.insert(next, (position, symbol));
+                queue.push_back(next);
+            }
+        }
+    }
+
+    None
+}
+
+//the automaton that accepts nothing
+fn nothing() -> Dfa {
+    Dfa {
+        transitions: vec![[0; ALPHABET_SIZE]],
+        accepting: vec![false],
+    }
+}
+
+fn shortest_accepted(dfa: &Dfa, state: usize) -> Option<String> {
+    dfa.shortest_disagreement(state, &nothing(), 0)
+}
+
+/// A characteristic sample for RPNI, in shortlex order: every state is
+/// reached by its shortest string and shown to be different from every
+/// other state that string or one of its one symbol extensions reaches.
+/// Oncina and García showed RPNI learns the automaton from any sample that
+/// contains one of these. `dfa` should be minimal.
+pub fn characteristic_sample(dfa: &Dfa) -> Vec<String> {
+    let live = dfa.live_states();
+
+    //the shortest string reaching each state, found breadth first
+    let mut access: Vec<Option<String>> = vec![None; dfa.len()];
+    access[0] = Some(String::new());
+    let mut queue = VecDeque::new();
+    queue.push_back(0);
+
+    while let Some(state) = queue.pop_front() {
+        let prefix = access[state].clone().unwrap_or_default();
+
+        for (&next, &c) in dfa.transitions[state].iter().zip(ALPHABET.iter()) {
+            if access[next].is_none() {
+                let mut text = prefix.clone();
+                text.push(c);
+
+                access[next] = Some(text);
+                queue.push_back(next);
+            }
+        }
+    }
+
+    //(the string, the state it reaches)
+    let short_prefixes: Vec<(String, usize)> = access
+        .iter()
+        .enumerate()
+        .filter(|&(state, _)| live[state])
+        .filter_map(|(state, text)| text.clone().map(|text| (text, state)))
+        .collect();
+
+    let mut kernel = short_prefixes.clone();
+    for &(ref prefix, state) in short_prefixes.iter() {
+        for (&next, &c) in dfa.transitions[state].iter().zip(ALPHABET.iter()) {
+            let mut text = prefix.clone();
+            text.push(c);
+
+            kernel.push((text, next));
+        }
+    }
+
+    let mut result = Vec::new();
+
+    for &(ref text, state) in kernel.iter() {
+        if let Some(suffix) = shortest_accepted(dfa, state) {
+            result.push(format!("{}{}", text, suffix));
+        }
+    }
+
+    for &(ref u, p) in short_prefixes.iter() {
+        for &(ref v, q) in kernel.iter() {
+            if p == q {
+                continue;
+            }
+
+            if let Some(suffix) = dfa.shortest_disagreement(p, dfa, q) {
+                result.push(format!("{}{}", u, suffix));
+                result.push(format!("{}{}", v, suffix));
+            }
+        }
+    }
+
+    result.sort_by(|a, b| (a.len(), a).cmp(&(b.len(), b)));
+    result.dedup();
+
+    result
+}
+
+#[cfg(test)]
+mod characteristic_sample {
+    use super::*;
+    use rand::{StdRng, SeedableRng};
+    use rpni::rpni;
+    use {edged_regex, generate_regex, unedged};
+
+    fn rpni_learns(target: &str) {
+        let regex = edged_regex(target).unwrap();
+        let dfa = Dfa::from_regex_str(target).unwrap().minimized();
+
+        let sample = characteristic_sample(&dfa);
+        let (positives, negatives): (Vec<String>, Vec<String>) =
+            sample.into_iter().partition(|text| regex.is_match(text));
+
+        assert!(rpni(&positives, &negatives).equivalent(&dfa), "{}", target);
+    }
+
+    #[test]
+    fn minimal() {
+        rpni_learns("0");
+    }
+    #[test]
+    fn found_example_from_readme() {
+        rpni_learns("1+|[012]*");
+    }
+    #[test]
+    fn groups() {
+        rpni_learns("(01)+2|3*");
+        rpni_learns("(0[12]*3)*");
+    }
+    #[test]
+    fn generated() {
+        for seed in 0..50 {
+            let seed_slice: &[_] = &[seed];
+            let mut rng: StdRng = SeedableRng::from_seed(seed_slice);
+
+            rpni_learns(unedged(generate_regex(&mut rng).as_str()));
+        }
+    }
+}
+
+#[cfg(test)]
+mod next_example {
+    use super::*;
+    use edged_regex;
+
+    #[test]
+    fn starts_with_a_match() {
+        let target = edged_regex("1+|[23]").unwrap();
+
+        for &kind in [TeacherKind::Counterexample,
+                      TeacherKind::Characteristic,
+                      TeacherKind::Splitting]
+                    .iter() {
+            let first = Teacher::new(kind).next_example(&target, &[], Some(&target));
+
+            assert_eq!(Some("1".to_string()), first);
+        }
+    }
+    #[test]
+    fn nothing_left_to_show() {
+        let target = edged_regex("0").unwrap();
+        let examples = [Example::new("0", &target)];
+
+        assert_eq!(None,
+                   Teacher::new(TeacherKind::Counterexample).next_example(&target,
+                                                                          &examples,
+                                                                          Some(&target)));
+    }
+    #[test]
+    fn splitting_narrows_things_down() {
+        let target = edged_regex("[01]+").unwrap();
+        let mut teacher = Teacher::new(TeacherKind::Splitting);
+        let mut examples: Vec<Example> = Vec::new();
+
+        while let Some(text) = teacher.next_example(&target, &examples, Some(&target)) {
+            assert!(examples.iter().all(|e| e.text != text));
+            examples.push(Example::new(&text, &target));
+        }
+
+        //every generated puzzle that's left is the same as the target
+        let hypotheses = generated_hypotheses();
+        let target_dfa = Dfa::from_regex_str("[01]+").unwrap();
+        for h in hypotheses.iter() {
+            if examples.iter().all(|e| h.accepts(&e.text) == e.matched) {
+                assert!(h.equivalent(&target_dfa));
+            }
+        }
+        assert!(examples.len() < 20, "{}", examples.len());
+    }
+    #[test]
+    fn skips_what_was_already_shown() {
+        let target = edged_regex("0|1").unwrap();
+        let guess = edged_regex("1").unwrap();
+        let mut teacher = Teacher::new(TeacherKind::Counterexample);
+
+        //the guess only gets "0" wrong, and that's been shown already
+        let examples = [Example::new("1", &target), Example::new("0", &target)];
+        assert_eq!(None, teacher.next_example(&target, &examples, Some(&guess)));
+
+        let examples = [Example::new("0", &target)];
+        assert_eq!(Some("1".to_string()), teacher.next_example(&target, &examples, None));
+
+        let examples = [Example::new("0", &target), Example::new("1", &target)];
+        assert_eq!(None, teacher.next_example(&target, &examples, None));
+    }
+}
@@ -0,0 +1,249 @@
+/// Text being typed in, with a caret and possibly a selection. Positions
+/// count characters, not bytes.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TextField {
+    text: String,
+    caret: usize,
+    //the other end of the selection, which is between it and the caret
+    anchor: Option<usize>,
+}
+
+impl TextField {
+    pub fn new() -> Self {
+        TextField::default()
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.text
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.text.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.text.chars().count()
+    }
+
+    pub fn caret(&self) -> usize {
+        self.caret
+    }
+
+    /// The start and end of the selection, if anything is selected.
+    pub fn selection(&self) -> Option<(usize, usize)> {
+        match self.anchor {
+            Some(anchor) if anchor != self.caret => {
+                Some((anchor.min(self.caret), anchor.max(self.caret)))
+            }
+            _ => None,
+        }
+    }
+
+    pub fn clear(&mut self) {
+        *self = TextField::default();
+    }
+
+    /// Types `s` in at the caret, replacing the selection if there is one.
+    pub fn insert_str(&mut self, s: &str) {
+        self.delete_selection();
+
+        let index = self.byte_index(self.caret);
+        self.text.insert_str(index, s);
+        self.caret += s.chars().count();
+    }
+
+    pub fn insert(&mut self, c: char) {
+        let mut buffer = [0; 4];
+        self.insert_str(c.encode_utf8(&mut buffer));
+    }
+
+    /// Deletes the selection, or else the character before the caret.
+    pub fn backspace(&mut self) {
+        if !self.delete_selection() && self.caret > 0 {
+            self.caret -= 1;
+            let index = self.byte_index(self.caret);
+            self.text.remove(index);
+        }
+    }
+
+    /// Deletes the selection, or else the character after the caret.
+    pub fn delete(&mut self) {
+        if !self.delete_selection() && self.caret < self.len() {
+            let index = self.byte_index(self.caret);
+            self.text.remove(index);
+        }
+    }
+
+    /// Moves the caret to `position`, keeping the end of the selection where
+    /// it was if `select` is set, like holding shift does.
+    pub fn move_caret(&mut self, position: usize, select: bool) {
+        if select {
+            if self.anchor.is_none() {
+                self.anchor = Some(self.caret);
+            }
+        } else {
+            self.anchor = None;
+        }
+
+        self.caret = position.min(self.len());
+    }
+
+    pub fn left(&mut self, select: bool) {
+        let position = match self.selection() {
+            Some((start, _)) if !select => start,
+            _ => self.caret.saturating_sub(1),
+        };
+
+        self.move_caret(position, select);
+    }
+
+    pub fn right(&mut self, select: bool) {
+        let position = match self.selection() {
+            Some((_, end)) if !select => end,
+            _ => self.caret + 1,
+        };
+
+        self.move_caret(position, select);
+    }
+
+    pub fn home(&mut self, select: bool) {
+        self.move_caret(0, select);
+    }
+
+    pub fn end(&mut self, select: bool) {
+        let len = self.len();
+        self.move_caret(len, select);
+    }
+
+    //returns whether there was anything to delete
+    fn delete_selection(&mut self) -> bool {
+        let result = match self.selection() {
+            Some((start, end)) => {
+                let (start_index, end_index) = (self.byte_index(start), self.byte_index(end));
+                self.text.replace_range(start_index..end_index, "");
+                self.caret = start;
+
+                true
+            }
+            None => false,
+        };
+
+        self.anchor = None;
+
+        result
+    }
+
+    fn byte_index(&self, position: usize) -> usize {
+        self.text
+            .char_indices()
+            .nth(position)
+            .map(|(i, _)| i)
+            .unwrap_or_else(|| self.text.len())
+    }
+}
+
+impl<'a> From<&'a str> for TextField {
+    fn from(text: &'a str) -> Self {
+        TextField {
+            text: text.to_string(),
+            caret: text.chars().count(),
+            anchor: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod insert_str {
+    use super::*;
+
+    #[test]
+    fn minimal() {
+        let mut field = TextField::new();
+        field.insert_str("0");
+
+        assert_eq!("0", field.as_str());
+        assert_eq!(1, field.caret());
+    }
+    #[test]
+    fn in_the_middle() {
+        let mut field = TextField::from("03");
+        field.left(false);
+        field.insert_str("12");
+
+        assert_eq!("0123", field.as_str());
+        assert_eq!(3, field.caret());
+    }
+    #[test]
+    fn replaces_the_selection() {
+        let mut field = TextField::from("0123");
+        field.home(false);
+        field.right(false);
+        field.end(true);
+        field.insert('3');
+
+        assert_eq!("03", field.as_str());
+        assert_eq!(None, field.selection());
+    }
+}
+
+#[cfg(test)]
+mod delete {
+    use super::*;
+
+    #[test]
+    fn forwards_and_backwards() {
+        let mut field = TextField::from("0123");
+        field.left(false);
+        field.left(false);
+
+        field.delete();
+        assert_eq!("013", field.as_str());
+
+        field.backspace();
+        assert_eq!("03", field.as_str());
+        assert_eq!(1, field.caret());
+    }
+    #[test]
+    fn at_the_ends() {
+        let mut field = TextField::from("0");
+        field.delete();
+        field.home(false);
+        field.backspace();
+
+        assert_eq!("0", field.as_str());
+    }
+    #[test]
+    fn selection() {
+        let mut field = TextField::from("0123");
+        field.left(true);
+        field.left(true);
+
+        assert_eq!(Some((2, 4)), field.selection());
+
+        field.backspace();
+        assert_eq!("01", field.as_str());
+    }
+}
+
+#[cfg(test)]
+mod move_caret {
+    use super::*;
+
+    #[test]
+    fn collapses_the_selection() {
+        let mut field = TextField::from("0123");
+        field.home(true);
+        field.right(false);
+
+        assert_eq!(None, field.selection());
+        assert_eq!(4, field.caret());
+    }
+    #[test]
+    fn stays_inside() {
+        let mut field = TextField::from("01");
+        field.right(false);
+        field.move_caret(10, false);
+
+        assert_eq!(2, field.caret());
+    }
+}
@@ -5,6 +5,12 @@ extern crate regex;
 use common::*;
 use common::Turn::*;
 
+mod widgets;
+use widgets::*;
+
+use std::path::Path;
+use std::time::{Duration, Instant};
+
 use regex::Regex;
 
 use rand::{StdRng, SeedableRng, Rng};
@@ -21,7 +27,7 @@ pub fn new_state(size: Size) -> State {
     let seed: &[_] = &[42];
     let rng: StdRng = SeedableRng::from_seed(seed);
 
-    make_state(size, false, rng)
+    make_state(size, false, rng, Mode::Master, LearnerKind::Heuristic)
 }
 #[cfg(not(debug_assertions))]
 #[no_mangle]
@@ -36,21 +42,47 @@ pub fn new_state(size: Size) -> State {
     let seed: &[_] = &[timestamp as usize];
     let rng: StdRng = SeedableRng::from_seed(seed);
 
-    make_state(size, true, rng)
+    make_state(size, true, rng, Mode::Master, LearnerKind::Heuristic)
 }
 
 
-fn make_state(size: Size, title_screen: bool, mut rng: StdRng) -> State {
+fn make_state(size: Size,
+              title_screen: bool,
+              mut rng: StdRng,
+              mode: Mode,
+              learner_kind: LearnerKind)
+              -> State {
     let regex = generate_regex(&mut rng);
 
     State {
         rng: rng,
         title_screen: title_screen,
-        text: String::new(),
+        setup_screen: false,
+        code_screen: false,
+        campaign_screen: false,
+        daily: None,
+        level: None,
+        campaign_choice: 0,
+        levels_finished: 0,
+        mode,
+        text: TextField::new(),
         regex,
         examples: Vec::new(),
-        guessed_regex: Regex::new("").unwrap(),
+        example_scroll: ScrollPosition::default(),
+        guessed_regex: None,
+        disagreement: None,
         turn: InProgress,
+        learner_kind,
+        learner: new_learner(learner_kind),
+        auto_answer: false,
+        //the player has to be able to guess from what they're shown
+        teacher: Teacher::new(if mode == Mode::Taught {
+                                  TeacherKind::Splitting
+                              } else {
+                                  TeacherKind::Counterexample
+                              }),
+        last_step: None,
+        message: None,
         ui_context: UIContext::new(),
     }
 }
@@ -59,493 +91,1992 @@ fn make_state(size: Size, title_screen: bool, mut rng: StdRng) -> State {
 //returns true if quit requested
 pub fn update_and_render(platform: &Platform, state: &mut State, events: &mut Vec<Event>) -> bool {
     if state.title_screen {
-
-        for event in events {
-            cross_mode_event_handling(platform, state, event);
-            match *event {
-                Event::Close |
-                Event::KeyPressed {
-                    key: KeyCode::Escape,
-                    ctrl: _,
-                    shift: _,
-                } => return true,
-                Event::KeyPressed {
-                    key: _,
-                    ctrl: _,
-                    shift: _,
-                } => state.title_screen = false,
-                _ => (),
+        title_update_and_render(platform, state, events)
+    } else if state.setup_screen {
+        setup_update_and_render(platform, state, events)
+    } else if state.code_screen {
+        code_update_and_render(platform, state, events)
+    } else if state.campaign_screen {
+        campaign_update_and_render(platform, state, events)
+    } else {
+        match state.mode {
+            Mode::Master | Mode::Challenge => game_update_and_render(platform, state, events),
+            Mode::Guesser | Mode::Race | Mode::Taught => {
+                guesser_update_and_render(platform, state, events)
             }
+            Mode::Labelling => labelling_update_and_render(platform, state, events),
+            Mode::Spectator => spectator_update_and_render(platform, state, events),
         }
+    }
+}
 
-        false
-    } else {
-        game_update_and_render(platform, state, events)
+//the size of the title screen's grid of buttons
+const MENU_W: i32 = 77;
+const MENU_H: i32 = 4 * BUTTON_H + 3;
+
+fn title_update_and_render(platform: &Platform,
+                           state: &mut State,
+                           events: &mut Vec<Event>)
+                           -> bool {
+    use common::Constraint::*;
+
+    let input = gather_input(platform, state, events);
+    if input.quit {
+        return true;
+    }
+
+    state.ui_context.frame_init();
+
+    let parts = column(padded(screen((platform.size)()), 1),
+                       &[Fill(1), Length(1), Fill(1), Length(MENU_H), Fill(1), Length(1)],
+                       1);
+    //the modes against the computer, the main ones, then the ones with a
+    //particular puzzle
+    let menu = row(anchored(parts[3], Size::new(MENU_W, MENU_H), Anchor::Center),
+                   &[Length(22), Fill(1), Length(16)],
+                   2);
+    let slots = |menu_column| column(menu_column, &[Length(BUTTON_H); 4], 1);
+    let (left, middle, right) = (slots(menu[0]), slots(menu[1]), slots(menu[2]));
+    //the main buttons are narrower than their column, but for the last one
+    let main_slot = |i: usize| anchored(middle[i], Size::new(25, BUTTON_H), Anchor::Center);
+
+    label(platform, parts[1], "Rezendo Master", Anchor::Center);
+
+    let master_spec = button_in(main_slot(0), "Be the master", widget_id(&["title", "master"]));
+    let guesser_spec = button_in(main_slot(1), "Guess the regex", widget_id(&["title", "guesser"]));
+
+    if do_button(platform,
+                 &mut state.ui_context,
+                 &master_spec,
+                 input.left_mouse_pressed,
+                 input.left_mouse_released) || input.any_key {
+        state.mode = Mode::Master;
+        state.title_screen = false;
+    }
+
+    let race_spec = button_in(main_slot(2), "Race the computer", widget_id(&["title", "race"]));
+
+    if do_button(platform,
+                 &mut state.ui_context,
+                 &guesser_spec,
+                 input.left_mouse_pressed,
+                 input.left_mouse_released) {
+        state.mode = Mode::Guesser;
+        state.title_screen = false;
+    }
+
+    if do_button(platform,
+                 &mut state.ui_context,
+                 &race_spec,
+                 input.left_mouse_pressed,
+                 input.left_mouse_released) {
+        state.mode = Mode::Race;
+        state.title_screen = false;
+
+        //the computer only gets to see the examples the player asks for
+        if state.learner_kind.is_active() {
+            state.learner_kind = state.learner_kind.next();
+        }
+        state.learner = new_learner(state.learner_kind);
+    }
+
+    let labelling_spec = button_in(middle[3],
+                                   "Be the master, without a regex",
+                                   widget_id(&["title", "labelling"]));
+
+    if do_button(platform,
+                 &mut state.ui_context,
+                 &labelling_spec,
+                 input.left_mouse_pressed,
+                 input.left_mouse_released) {
+        state.mode = Mode::Labelling;
+        state.title_screen = false;
+
+        //there's nobody to answer the questions but the master, and they
+        //already have enough to do
+        if state.learner_kind.is_active() {
+            state.learner_kind = state.learner_kind.next();
+        }
+        state.learner = new_learner(state.learner_kind);
+    }
+
+    let code_spec = button_in(right[0], "Enter code", widget_id(&["title", "code"]));
+
+    if do_button(platform,
+                 &mut state.ui_context,
+                 &code_spec,
+                 input.left_mouse_pressed,
+                 input.left_mouse_released) {
+        state.code_screen = true;
+        state.title_screen = false;
+        state.text.clear();
+        state.message = None;
+        //otherwise this frame's key presses would start typing the code
+        return false;
+    }
+
+    let challenge_spec = button_in(right[2], "Challenge", widget_id(&["title", "challenge"]));
+
+    if do_button(platform,
+                 &mut state.ui_context,
+                 &challenge_spec,
+                 input.left_mouse_pressed,
+                 input.left_mouse_released) {
+        state.mode = Mode::Challenge;
+        state.title_screen = false;
+
+        //questions would use up the budget without the master choosing them
+        if state.learner_kind.is_active() {
+            state.learner_kind = state.learner_kind.next();
+        }
+        state.learner = new_learner(state.learner_kind);
+    }
+
+    let spectator_spec = button_in(left[0],
+                                   "Watch the computers",
+                                   widget_id(&["title", "spectator"]));
+
+    if do_button(platform,
+                 &mut state.ui_context,
+                 &spectator_spec,
+                 input.left_mouse_pressed,
+                 input.left_mouse_released) {
+        state.mode = Mode::Spectator;
+        state.title_screen = false;
+        state.last_step = Some(Instant::now());
+    }
+
+    let taught_spec = button_in(left[1], "Taught by computer", widget_id(&["title", "taught"]));
+
+    if do_button(platform,
+                 &mut state.ui_context,
+                 &taught_spec,
+                 input.left_mouse_pressed,
+                 input.left_mouse_released) {
+        state.mode = Mode::Taught;
+        state.title_screen = false;
+        state.teacher = Teacher::new(TeacherKind::Splitting);
+    }
+
+    let campaign_spec = button_in(right[3], "Campaign", widget_id(&["title", "campaign"]));
+
+    if do_button(platform,
+                 &mut state.ui_context,
+                 &campaign_spec,
+                 input.left_mouse_pressed,
+                 input.left_mouse_released) {
+        state.campaign_screen = true;
+        state.title_screen = false;
+        state.message = None;
+        state.levels_finished = load_progress(Path::new(PROGRESS_FILE));
+        //the first level that hasn't been finished yet
+        state.campaign_choice = state.levels_finished.min(LEVELS.len() - 1);
+    }
+
+    let daily_spec = button_in(right[1], "Daily puzzle", widget_id(&["title", "daily"]));
+
+    if do_button(platform,
+                 &mut state.ui_context,
+                 &daily_spec,
+                 input.left_mouse_pressed,
+                 input.left_mouse_released) {
+        let day = today();
+        let learner_kind = state.learner_kind;
+
+        *state = make_state((platform.size)(), false, state.rng, Mode::Master, learner_kind);
+        state.regex = daily_regex(day);
+        state.daily = Some(day);
     }
+
+    label(platform, parts[5], "(or press any key to be the master)", Anchor::Center);
+
+    false
 }
 
 pub fn game_update_and_render(platform: &Platform,
                               state: &mut State,
                               events: &mut Vec<Event>)
                               -> bool {
-    let mut left_mouse_pressed = false;
-    let mut left_mouse_released = false;
-
-    let mut backspace_key = false;
-    let mut enter_key = false;
-
-    let mut num_key = [false, false, false, false];
-
-    for event in events {
-        cross_mode_event_handling(platform, state, event);
+    use common::Constraint::*;
 
-        match *event {
-            Event::KeyPressed {
-                key: KeyCode::MouseLeft,
-                ctrl: _,
-                shift: _,
-            } => {
-                left_mouse_pressed = true;
-            }
-            Event::KeyReleased {
-                key: KeyCode::MouseLeft,
-                ctrl: _,
-                shift: _,
-            } => {
-                left_mouse_released = true;
-            }
-            Event::Close |
-            Event::KeyPressed {
-                key: KeyCode::Escape,
-                ctrl: _,
-                shift: _,
-            } => return true,
-            Event::KeyReleased {
-                key: KeyCode::Row0,
-                ctrl: _,
-                shift: _,
-            } |
-            Event::KeyReleased {
-                key: KeyCode::Num0,
-                ctrl: _,
-                shift: _,
-            } => {
-                num_key[0] = true;
-            }
-            Event::KeyReleased {
-                key: KeyCode::Row1,
-                ctrl: _,
-                shift: _,
-            } |
-            Event::KeyReleased {
-                key: KeyCode::Num1,
-                ctrl: _,
-                shift: _,
-            } => {
-                num_key[1] = true;
-            }
-            Event::KeyReleased {
-                key: KeyCode::Row2,
-                ctrl: _,
-                shift: _,
-            } |
-            Event::KeyReleased {
-                key: KeyCode::Num2,
-                ctrl: _,
-                shift: _,
-            } => {
-                num_key[2] = true;
-            }
-            Event::KeyReleased {
-                key: KeyCode::Row3,
-                ctrl: _,
-                shift: _,
-            } |
-            Event::KeyReleased {
-                key: KeyCode::Num3,
-                ctrl: _,
-                shift: _,
-            } => {
-                num_key[3] = true;
-            }
-            Event::KeyReleased {
-                key: KeyCode::Backspace,
-                ctrl: _,
-                shift: _,
-            } => {
-                backspace_key = true;
-            }
-            Event::KeyReleased {
-                key: KeyCode::Enter,
-                ctrl: _,
-                shift: _,
-            } => {
-                enter_key = true;
-            }
-            _ => (),
-        }
+    let input = gather_input(platform, state, events);
+    if input.quit {
+        return true;
     }
+    let Input {
+        left_mouse_pressed,
+        left_mouse_released,
+        enter_key,
+        ..
+    } = input;
 
     state.ui_context.frame_init();
 
-    for index in 0..4 {
-        let i = index as i32;
+    let layout = game_layout((platform.size)(), DIGIT_KEYBOARD_H);
+
+    text_field(platform,
+               &mut state.ui_context,
+               &entry_spec(&layout, is_example_char),
+               &mut state.text,
+               &input);
 
-        let spec = ButtonSpec {
-            x: 20 + (i * 10),
-            y: 20,
-            w: 5,
-            h: 3,
-            text: index.to_string(),
-            id: i + 1,
-        };
+    let keys = row(layout.keyboard,
+                   &[Length(5), Length(5), Length(5), Length(5), Length(7)],
+                   1);
+
+    for (i, &key) in keys.iter().take(4).enumerate() {
+        let digit = i.to_string();
+        let spec = button_in(key, &digit, widget_id(&["keyboard", &digit]));
 
         if do_button(platform,
                      &mut state.ui_context,
                      &spec,
                      left_mouse_pressed,
-                     left_mouse_released) || num_key[index] {
-            state.text.push_str(&index.to_string());
+                     left_mouse_released) {
+            state.text.insert_str(&digit);
         }
     }
 
-    let backspace_spec = ButtonSpec {
-        x: 20 + (4 * 10),
-        y: 20,
-        w: 7,
-        h: 3,
-        text: "⌫".to_string(),
-        id: 10,
-    };
+    let backspace_spec = button_in(keys[4], "⌫", widget_id(&["keyboard", "backspace"]));
 
     if do_button(platform,
                  &mut state.ui_context,
                  &backspace_spec,
                  left_mouse_pressed,
-                 left_mouse_released) || backspace_key {
-        state.text.pop();
-    }
-    let enter_spec = ButtonSpec {
-        x: 18 + (4 * 10),
-        y: 25,
-        w: 11,
-        h: 3,
-        text: "Submit".to_string(),
-        id: 12,
-    };
+                 left_mouse_released) {
+        state.text.backspace();
+    }
+
+    let actions = row(layout.actions, &[Length(11)], 1);
+    let enter_spec = button_in(actions[0], "Submit", widget_id(&["game", "submit"]));
 
     if do_button(platform,
                  &mut state.ui_context,
                  &enter_spec,
                  left_mouse_pressed,
                  left_mouse_released) || enter_key {
-        if state.examples.iter().any(|e| e.text == state.text) {
+        if state.examples.iter().any(|e| e.text == state.text.as_str()) {
             //TODO note example was already added
+        } else if state.mode == Mode::Challenge && state.turn != InProgress {
+            //the challenge is over
         } else {
-            state.examples.push(Example::new(&state.text, &state.regex));
-
-            let mut guessed_regex;
-
-            if state.guessed_regex.as_str().is_empty() {
-                guessed_regex = state
-                    .examples
-                    .iter()
-                    .fold(String::new(), |mut acc, ex| {
-                        if ex.matched {
-                            if !acc.is_empty() {
-                                acc.push('|');
-                            };
-                            acc.push('(');
-                            acc.push_str(&ex.text);
-                            acc.push(')');
-
-                        };
-                        acc
-                    });
-            } else {
-                guessed_regex = state.guessed_regex.as_str().to_owned();
-
-                let mut sub_regexes = get_sub_regexes(&guessed_regex);
-                let new_example = state.examples.last().unwrap();
-                if new_example.matched {
-                    //extend a regex to make the new example match
-                    for s in sub_regexes.iter_mut() {
-                        if let Some(extended) = extend_to_fit(s, new_example) {
-                            *s = extended;
-                            break;
-                        }
-                    }
-                } else {
-                    //make sure none of the sub_regexes match the new example
-                    for s in sub_regexes.iter_mut() {
-                        if let Some(contracted) = contract_to_avoid(s, new_example) {
-                            *s = contracted;
-                        }
-                    }
-                }
+            state.examples.push(Example::new(state.text.as_str(), &state.regex));
+            state.learner.observe(state.examples.last().unwrap());
 
-                guessed_regex = collect_sub_regexes(sub_regexes);
+            //the learner has something new to go on, and the example may
+            //well have answered what it was asking
+            if let Query(_) | Conjecture = state.turn {
+                state.turn = InProgress;
             }
 
-            guessed_regex = simplify_regex(&guessed_regex);
+            update_guess(state);
+        }
 
-            if let Ok(regex) = edged_regex(&guessed_regex) {
-                state.guessed_regex = regex;
-            } else {
-                if cfg!(debug_assertions) {
-                    println!("bad guess: {}", guessed_regex);
+        state.text.clear();
+    }
+
+    let sidebar = column(layout.sidebar,
+                         &[Fill(1), Length(BUTTON_H), Length(1), Length(BUTTON_H)],
+                         1);
+
+    let learner_spec = button_in(sidebar[3],
+                                 state.learner_kind.name(),
+                                 widget_id(&["game", "learner"]));
+
+    if do_button(platform,
+                 &mut state.ui_context,
+                 &learner_spec,
+                 left_mouse_pressed,
+                 left_mouse_released) {
+        //the new learner gets to see everything the old one did
+        state.learner_kind = state.learner_kind.next();
+        if state.mode == Mode::Challenge && state.learner_kind.is_active() {
+            state.learner_kind = state.learner_kind.next();
+        }
+        state.learner = new_learner(state.learner_kind);
+        for e in state.examples.iter() {
+            state.learner.observe(e);
+        }
+
+        if let Query(_) | Conjecture = state.turn {
+            state.turn = InProgress;
+        }
+
+        update_guess(state);
+    }
+
+    let auto_answer_spec = CheckboxSpec {
+        x: sidebar[2].top_left.x,
+        y: sidebar[2].top_left.y,
+        text: "Auto answer".to_string(),
+        id: widget_id(&["game", "auto answer"]),
+    };
+
+    checkbox(platform,
+             &mut state.ui_context,
+             &auto_answer_spec,
+             &mut state.auto_answer,
+             &input);
+
+    let custom_spec = button_in(sidebar[1], "Own regex", widget_id(&["game", "own regex"]));
+
+    //the difficulty is part of the challenge's score, so it has to be a
+    //generated puzzle
+    if state.mode != Mode::Challenge &&
+       do_button(platform,
+                 &mut state.ui_context,
+                 &custom_spec,
+                 left_mouse_pressed,
+                 left_mouse_released) {
+        state.setup_screen = true;
+        state.text.clear();
+        state.message = None;
+    }
+
+    if let InProgress = state.turn {
+        match state.learner.question() {
+            Some(Question::IsMember(text)) => state.turn = Query(text),
+            Some(Question::IsEquivalent) => {
+                update_guess(state);
+
+                if let InProgress = state.turn {
+                    state.turn = Conjecture;
+                }
+            }
+            None => {
+                if state.learner.gave_up() {
+                    update_guess(state);
                 }
             }
         }
+    }
 
-        //TODO keep history and allow scrolling up and down
-        state.text.clear();
+    if let Some(day) = state.daily {
+        print_line(platform,
+                   layout.status,
+                   0,
+                   &format!("daily puzzle for {}", date_string(day)));
+    }
+    if let Some(level) = state.level {
+        print_line(platform,
+                   layout.status,
+                   0,
+                   &format!("level {}: {}", level + 1, LEVELS[level].name));
     }
+    if state.mode == Mode::Challenge {
+        print_line(platform,
+                   layout.status,
+                   0,
+                   &format!("examples left: {}",
+                            EXAMPLE_BUDGET.saturating_sub(state.examples.len())));
+    }
+
+    print_line(platform, layout.regex, 0, unedged(state.regex.as_str()));
+    print_line(platform, layout.guess, 0, guess_text(&state.guessed_regex));
 
-    (platform.print_xy)(20,
-                        5,
-                        state.regex.as_str().trim_matches(|c| c == '^' || c == '$'));
-    (platform.print_xy)(20,
-                        7,
-                        state
-                            .guessed_regex
-                            .as_str()
-                            .trim_matches(|c| c == '^' || c == '$'));
+    if let Some(ref text) = state.disagreement {
+        print_line(platform,
+                   layout.notes,
+                   0,
+                   &format!("where they still disagree: {}",
+                            if text.is_empty() { "ε" } else { text }));
+    }
 
-    let current_example = Example::new(&state.text, &state.regex);
+    let current_example = Example::new(state.text.as_str(), &state.regex);
 
-    current_example.print_xy(platform, 7, 10);
+    current_example.print_mark_xy(platform, layout.entry.top_left.x, layout.entry.top_left.y);
 
 
-    //TODO pagination/scrolling
-    for (index, e) in state.examples.iter().enumerate() {
-        let i = index as i32;
+    let wrong = guess_misclassified(state);
 
-        e.print_xy(platform, 50, (2 * i) + 3);
+    if !wrong.is_empty() {
+        print_line(platform,
+                   layout.notes,
+                   1,
+                   &format!("the guess gets {} of the examples wrong (marked ≠)",
+                            wrong.len()));
     }
 
-    match state.turn {
+    example_list(platform,
+                 layout.examples,
+                 &mut state.example_scroll,
+                 &state.examples,
+                 &wrong,
+                 &input);
+
+    //the question goes on the first line, and the answers under it
+    let answers = column(layout.turn, &[Length(1), Length(BUTTON_H)], 1)[1];
+
+    match state.turn.clone() {
         InProgress => {
-            //TODO fuzzy matching or keep simplifying?
-            if state.regex.as_str() == state.guessed_regex.as_str() {
-                state.turn = Finished;
+            if state.learner.gave_up() {
+                print_line(platform, layout.turn, 0, GAVE_UP_MESSAGE);
             }
         }
-        Finished => {
-            (platform.print_xy)(20, 15, "They figured it out!");
-
-            let new_spec = ButtonSpec {
-                x: 45,
-                y: 14,
-                w: 12,
-                h: 3,
-                text: "New Puzzle".to_string(),
-                id: 220,
+        Query(text) => {
+            print_line(platform,
+                       layout.turn,
+                       0,
+                       &format!("Is {} in your language?",
+                                if text.is_empty() { "ε" } else { &text }));
+
+            let answer = if state.auto_answer {
+                Some(state.regex.is_match(&text))
+            } else {
+                let buttons = row(answers, &[Length(7), Length(7)], 1);
+                let yes_spec = button_in(buttons[0], "Yes", widget_id(&["game", "yes"]));
+                let no_spec = button_in(buttons[1], "No", widget_id(&["game", "no"]));
+
+                if do_button(platform,
+                             &mut state.ui_context,
+                             &yes_spec,
+                             left_mouse_pressed,
+                             left_mouse_released) {
+                    Some(true)
+                } else if do_button(platform,
+                                    &mut state.ui_context,
+                                    &no_spec,
+                                    left_mouse_pressed,
+                                    left_mouse_released) {
+                    Some(false)
+                } else {
+                    None
+                }
             };
 
-            if do_button(platform,
+            if let Some(matched) = answer {
+                //the master's word is final, even if it disagrees with the regex
+                state.examples.push(Example { text, matched });
+                state.learner.observe(state.examples.last().unwrap());
+
+                state.turn = InProgress;
+            }
+        }
+        Conjecture => {
+            print_line(platform, layout.turn, 0, "Is that your regex?");
+
+            //the game already knows the guess is wrong, otherwise the turn
+            //would be finished, so the only answer is where it goes wrong
+            let counterexample = state.disagreement.clone();
+
+            let no_spec = button_in(row(answers, &[Length(12)], 1)[0],
+                                    "No",
+                                    widget_id(&["game", "no"]));
+
+            if state.auto_answer ||
+               do_button(platform,
                          &mut state.ui_context,
-                         &new_spec,
+                         &no_spec,
                          left_mouse_pressed,
                          left_mouse_released) {
-                *state = make_state((platform.size)(), false, state.rng);
+                //it can already be an example if the master answered a query
+                //against their own regex, and then there's nothing new to show
+                if let Some(text) = counterexample {
+                    if !state.examples.iter().any(|e| e.text == text) {
+                        state.examples.push(Example::new(&text, &state.regex));
+                        state.learner.observe(state.examples.last().unwrap());
+                    }
+                }
+
+                state.turn = InProgress;
             }
         }
+        Finished | OutOfExamples => {
+            results_update_and_render(platform, state, &input);
+        }
     }
 
     false
 }
 
 
-fn extend_to_fit(regex_str: &str, example: &Example) -> Option<String> {
-    println!("extend {}", regex_str);
-    if let Ok(regex) = edged_regex(regex_str) {
-        if regex.is_match(&example.text) {
-            None
-        } else {
-            //TODO handle more cases
-            let mut try = format!("({}+)", regex_str);
-            println!("{}", try);
-
-            if edged_regex(&try)
-                   .map(|r| r.is_match(&example.text))
-                   .unwrap_or(false) {
-                return Some(try);
-            }
-
-            try = format!("({}*)", regex_str);
-
-            if edged_regex(&try)
-                   .map(|r| r.is_match(&example.text))
-                   .unwrap_or(false) {
-                return Some(try);
-            }
-
-            if cfg!(debug_assertions) {
-                println!("default extension");
-            }
+//what the master gets to see once the computer has figured it out, or the
+//challenge is over
+fn results_update_and_render(platform: &Platform, state: &mut State, input: &Input) {
+    let mut lines = Vec::new();
 
-            Some(format!("{}|{}", regex_str, example.text))
-        }
+    if state.turn == OutOfExamples {
+        lines.push("Out of examples!".to_string());
     } else {
-        Some(String::from(".*"))
-    }
-}
-fn contract_to_avoid(regex_str: &str, example: &Example) -> Option<String> {
-    if let Ok(regex) = edged_regex(regex_str) {
-        if regex.is_match(&example.text) {
-            //TODO handle more cases
-            let len = example.text.len();
-
-            if example
-                   .text
-                   .chars()
-                   .nth(len - 2)
-                   .map(|c| c == '*' || c == '+')
-                   .unwrap_or(false) {
-                let mut try = example.text.to_owned();
-                try.remove(len - 2);
-                if edged_regex(&try)
-                       .map(|r| !r.is_match(&example.text))
-                       .unwrap_or(false) {
-                    return Some(try);
-                }
-            }
+        lines.push(format!("They figured it out with {} examples!", state.examples.len()));
+    }
 
-            None
-        } else {
-            None
-        }
-    } else {
-        Some(String::from(""))
+    if let Some(ref message) = state.message {
+        lines.push(message.clone());
     }
-}
 
-fn cross_mode_event_handling(platform: &Platform, state: &mut State, event: &Event) {
-    match *event {
-        Event::KeyPressed {
-            key: KeyCode::R,
-            ctrl: true,
-            shift: _,
-        } => {
-            println!("reset");
-            *state = new_state((platform.size)());
+    if state.turn == OutOfExamples {
+        lines.push("score:     0".to_string());
+    } else if state.mode == Mode::Challenge {
+        if let Some(score) = Score::new(&state.regex, state.examples.len()) {
+            lines.extend(score.breakdown());
         }
-        _ => (),
     }
-}
-
-pub struct ButtonSpec {
-    pub x: i32,
-    pub y: i32,
-    pub w: i32,
-    pub h: i32,
-    pub text: String,
-    pub id: i32,
-}
 
-//calling this once will swallow multiple clicks on the button. We could either
-//pass in and return the number of clicks to fix that, or this could simply be
-//called multiple times per frame (once for each click).
-fn do_button(platform: &Platform,
-             context: &mut UIContext,
-             spec: &ButtonSpec,
-             left_mouse_pressed: bool,
-             left_mouse_released: bool)
-             -> bool {
-    let mut result = false;
+    lines.push(format!("puzzle code: {}", puzzle_code(state)));
 
-    let mouse_pos = (platform.mouse_position)();
-    let inside = inside_rect(mouse_pos, spec.x, spec.y, spec.w, spec.h);
-    let id = spec.id;
+    //campaign puzzles lead on to the next level instead
+    let next_level = state.level.map(|level| (level + 1).min(LEVELS.len() - 1));
 
-    if context.active == id {
-        if left_mouse_released {
-            result = context.hot == id && inside;
+    let spec = DialogSpec {
+        lines: &lines,
+        buttons: &[if next_level.is_some() && next_level != state.level {
+                       "Next level"
+                   } else {
+                       "New Puzzle"
+                   }],
+        id: widget_id(&["results"]),
+    };
 
-            context.set_not_active();
-        }
-    } else if context.hot == id {
-        if left_mouse_pressed {
-            context.set_active(id);
+    if dialog(platform, &mut state.ui_context, &spec, input).is_some() {
+        match next_level {
+            Some(level) => start_level(platform, state, level),
+            None => {
+                *state = make_state((platform.size)(),
+                                    false,
+                                    state.rng,
+                                    state.mode,
+                                    state.learner_kind)
+            }
         }
     }
+}
 
-    if inside {
-        context.set_next_hot(id);
-    }
+//the symbols besides the digits a guess can use, in the order of their buttons
+const REGEX_SYMBOLS: [char; 9] = ['[', ']', '(', ')', '.', '|', '*', '+', '?'];
 
-    if context.active == id && left_mouse_pressed {
-        draw_rect_with(platform,
-                       spec.x,
-                       spec.y,
-                       spec.w,
-                       spec.h,
-                       ["╔", "═", "╕", "║", "│", "╙", "─", "┘"]);
-    } else if context.hot == id {
-        draw_rect_with(platform,
-                       spec.x,
-                       spec.y,
-                       spec.w,
-                       spec.h,
-                       ["┌", "─", "╖", "│", "║", "╘", "═", "╝"]);
-    } else {
-        draw_rect(platform, spec.x, spec.y, spec.w, spec.h);
+pub fn guesser_update_and_render(platform: &Platform,
+                                 state: &mut State,
+                                 events: &mut Vec<Event>)
+                                 -> bool {
+    use common::Constraint::*;
+
+    let input = gather_input(platform, state, events);
+    if input.quit {
+        return true;
     }
 
-    print_centered_line(platform, spec.x, spec.y, spec.w, spec.h, &spec.text);
+    state.ui_context.frame_init();
 
-    return result;
-}
+    let layout = game_layout((platform.size)(), REGEX_KEYBOARD_H);
 
-pub fn inside_rect(point: Point, x: i32, y: i32, w: i32, h: i32) -> bool {
-    x <= point.x && y <= point.y && point.x < x + w && point.y < y + h
-}
+    regex_keyboard(platform, state, &layout, &input);
 
-fn print_centered_line(platform: &Platform, x: i32, y: i32, w: i32, h: i32, text: &str) {
-    let x_ = {
-        let rect_middle = x + (w / 2);
+    let actions = row(layout.actions, &[Length(14), Length(11), Length(20)], 1);
 
-        rect_middle - (text.chars().count() as f32 / 2.0) as i32
-    };
+    let test_spec = button_in(actions[0], "Test", widget_id(&["guesser", "test"]));
 
-    let y_ = y + (h / 2);
+    if state.mode == Mode::Taught {
+        let show_spec = button_in(actions[0], "Show another", widget_id(&["guesser", "show"]));
 
-    (platform.print_xy)(x_, y_, &text);
-}
+        //the teacher starts things off without being asked
+        if state.turn == InProgress &&
+           (state.examples.is_empty() ||
+            do_button(platform,
+                      &mut state.ui_context,
+                      &show_spec,
+                      input.left_mouse_pressed,
+                      input.left_mouse_released)) {
+            match state.teacher.next_example(&state.regex,
+                                             &state.examples,
+                                             state.guessed_regex.as_ref()) {
+                Some(text) => {
+                    let example = Example::new(&text, &state.regex);
 
+                    state.message = None;
+                    add_shared_example(state, example);
+                }
+                None => {
+                    state.message = Some("The teacher has nothing else to show you, so guess"
+                                             .to_string());
+                }
+            }
+        }
+    } else if do_button(platform,
+                        &mut state.ui_context,
+                        &test_spec,
+                        input.left_mouse_pressed,
+                        input.left_mouse_released) || input.enter_key {
+        if !state.text.as_str().chars().all(|c| ALPHABET.contains(&c)) {
+            state.message = Some("Only the digits 0 to 3 can be tested".to_string());
+        } else {
+            let example = Example::new(state.text.as_str(), &state.regex);
 
-fn draw_rect(platform: &Platform, x: i32, y: i32, w: i32, h: i32) {
-    draw_rect_with(platform,
-                   x,
-                   y,
-                   w,
-                   h,
-                   ["┌", "─", "┐", "│", "│", "└", "─", "┘"]);
-}
+            state.message = None;
+            add_shared_example(state, example);
+            state.text.clear();
+        }
+    }
 
-fn draw_rect_with(platform: &Platform, x: i32, y: i32, w: i32, h: i32, edges: [&str; 8]) {
-    (platform.clear)(Some(Rect::from_values(x, y, w, h)));
+    let guess_spec = button_in(actions[1], "Guess", widget_id(&["guesser", "guess"]));
 
-    let right = x + w - 1;
-    let bottom = y + h - 1;
-    // top
-    (platform.print_xy)(x, y, edges[0]);
-    for i in (x + 1)..right {
-        (platform.print_xy)(i, y, edges[1]);
+    if state.turn == InProgress &&
+       (do_button(platform,
+                  &mut state.ui_context,
+                  &guess_spec,
+                  input.left_mouse_pressed,
+                  input.left_mouse_released) ||
+        (state.mode == Mode::Taught && input.enter_key)) {
+        judge_guess(state);
     }
-    (platform.print_xy)(right, y, edges[2]);
 
-    // sides
-    for i in (y + 1)..bottom {
-        (platform.print_xy)(x, i, edges[3]);
-        (platform.print_xy)(right, i, edges[4]);
-    }
+    if state.mode == Mode::Race {
+        let opponent_spec = button_in(actions[2],
+                                      &format!("vs {}", state.learner_kind.name()),
+                                      widget_id(&["guesser", "opponent"]));
+
+        if do_button(platform,
+                     &mut state.ui_context,
+                     &opponent_spec,
+                     input.left_mouse_pressed,
+                     input.left_mouse_released) {
+            state.learner_kind = state.learner_kind.next();
+            if state.learner_kind.is_active() {
+                state.learner_kind = state.learner_kind.next();
+            }
+
+            state.learner = new_learner(state.learner_kind);
+            for e in state.examples.iter() {
+                state.learner.observe(e);
+            }
+        }
 
-    //bottom
-    (platform.print_xy)(x, bottom, edges[5]);
-    for i in (x + 1)..right {
-        (platform.print_xy)(i, bottom, edges[6]);
+        print_line(platform,
+                   layout.notes,
+                   1,
+                   &format!("the computer guesses: {}",
+                            guess_text(&state.learner.hypothesis())));
     }
-    (platform.print_xy)(right, bottom, edges[7]);
+
+    if state.turn == Finished {
+        print_line(platform, layout.regex, 0, unedged(state.regex.as_str()));
+    } else {
+        print_line(platform, layout.regex, 0, "the hidden regex is a secret");
+    }
+    print_line(platform, layout.guess, 0, guess_text(&state.guessed_regex));
+
+    if let Some(ref message) = state.message {
+        print_line(platform, layout.notes, 0, message);
+    }
+
+
+    example_list(platform,
+                 layout.examples,
+                 &mut state.example_scroll,
+                 &state.examples,
+                 &[],
+                 &input);
+
+    if state.turn == Finished {
+        print_line(platform,
+                   layout.turn,
+                   0,
+                   &format!("puzzle code: {}", puzzle_code(state)));
+
+        let new_spec = button_in(new_puzzle_rect(&layout),
+                                 "New Puzzle",
+                                 widget_id(&["game", "new puzzle"]));
+
+        if do_button(platform,
+                     &mut state.ui_context,
+                     &new_spec,
+                     input.left_mouse_pressed,
+                     input.left_mouse_released) {
+            *state = make_state((platform.size)(),
+                                false,
+                                state.rng,
+                                state.mode,
+                                state.learner_kind);
+        }
+    }
+
+    false
+}
+
+//lets the master type in their own regex to start a new puzzle with
+fn setup_update_and_render(platform: &Platform,
+                           state: &mut State,
+                           events: &mut Vec<Event>)
+                           -> bool {
+    use common::Constraint::*;
+
+    let input = gather_input(platform, state, events);
+    if input.quit {
+        return true;
+    }
+
+    state.ui_context.frame_init();
+
+    let layout = game_layout((platform.size)(), REGEX_KEYBOARD_H);
+
+    regex_keyboard(platform, state, &layout, &input);
+
+    let actions = row(layout.actions, &[Length(11), Length(11)], 1);
+    let start_spec = button_in(actions[0], "Start", widget_id(&["setup", "start"]));
+    let cancel_spec = button_in(actions[1], "Cancel", widget_id(&["setup", "cancel"]));
+
+    if do_button(platform,
+                 &mut state.ui_context,
+                 &start_spec,
+                 input.left_mouse_pressed,
+                 input.left_mouse_released) || input.enter_key {
+        match parse_typed_regex(state.text.as_str()) {
+            Ok(regex) => {
+                *state = make_state((platform.size)(),
+                                    false,
+                                    state.rng,
+                                    state.mode,
+                                    state.learner_kind);
+                state.regex = regex;
+            }
+            Err(error) => {
+                state.message = Some(format!("That isn't a regex: {}", error));
+            }
+        }
+    }
+
+    if do_button(platform,
+                 &mut state.ui_context,
+                 &cancel_spec,
+                 input.left_mouse_pressed,
+                 input.left_mouse_released) {
+        state.setup_screen = false;
+        state.text.clear();
+        state.message = None;
+    }
+
+    //there's no regex or guess yet, so the instructions go where they would
+    print_line(platform,
+               layout.regex,
+               0,
+               "Type the regex for the computer to figure out");
+    print_line(platform,
+               layout.guess,
+               0,
+               "using the digits 0 to 3 and any of [ ] ( ) . | * + ?");
+
+    if let Some(ref message) = state.message {
+        print_line(platform, layout.notes, 0, message);
+    }
+
+
+    false
+}
+
+//how long the spectator mode waits before showing the next example
+const STEP_INTERVAL_MS: u64 = 500;
+
+//everyone the spectator mode can pair up, in the order they're listed
+const TEACHER_KINDS: [TeacherKind; 3] = [TeacherKind::Counterexample,
+                                         TeacherKind::Characteristic,
+                                         TeacherKind::Splitting];
+const LEARNER_KINDS: [LearnerKind; 4] = [LearnerKind::Heuristic,
+                                         LearnerKind::Rpni,
+                                         LearnerKind::LStar,
+                                         LearnerKind::Enumerative];
+
+pub fn spectator_update_and_render(platform: &Platform,
+                                   state: &mut State,
+                                   events: &mut Vec<Event>)
+                                   -> bool {
+    use common::Constraint::*;
+
+    let input = gather_input(platform, state, events);
+    if input.quit {
+        return true;
+    }
+    let Input {
+        left_mouse_pressed,
+        left_mouse_released,
+        ..
+    } = input;
+
+    state.ui_context.frame_init();
+
+    let layout = game_layout((platform.size)(), 0);
+
+    let actions = row(layout.actions, &[Length(11), Length(11)], 1);
+    //each choice has its label over it
+    let sidebar = column(layout.sidebar,
+                         &[Fill(1),
+                           Length(1),
+                           Length(TEACHER_KINDS.len() as i32),
+                           Length(1),
+                           Length(1),
+                           Length(LEARNER_KINDS.len() as i32)],
+                         0);
+
+    let play_spec = button_in(actions[0], "Play", widget_id(&["spectator", "play"]));
+    let step_spec = button_in(actions[1], "Step", widget_id(&["spectator", "step"]));
+    let new_spec = button_in(new_puzzle_rect(&layout),
+                             "New Puzzle",
+                             widget_id(&["game", "new puzzle"]));
+
+    let mut playing = state.last_step.is_some();
+
+    if toggle(platform,
+              &mut state.ui_context,
+              &play_spec,
+              &mut playing,
+              &input) {
+        state.last_step = if playing { Some(Instant::now()) } else { None };
+    }
+
+    let step_due = state.last_step
+        .map(|last| last.elapsed() >= Duration::from_millis(STEP_INTERVAL_MS))
+        .unwrap_or(false);
+
+    if do_button(platform,
+                 &mut state.ui_context,
+                 &step_spec,
+                 left_mouse_pressed,
+                 left_mouse_released) || step_due {
+        if state.last_step.is_some() {
+            state.last_step = Some(Instant::now());
+        }
+
+        if state.turn == InProgress {
+            spectator_step(state);
+        }
+    }
+
+    if do_button(platform,
+                 &mut state.ui_context,
+                 &new_spec,
+                 left_mouse_pressed,
+                 left_mouse_released) {
+        let teacher_kind = state.teacher.kind;
+
+        *state = make_state((platform.size)(),
+                            false,
+                            state.rng,
+                            state.mode,
+                            state.learner_kind);
+        state.teacher = Teacher::new(teacher_kind);
+        state.last_step = Some(Instant::now());
+    }
+
+    let teacher_names: Vec<&str> = TEACHER_KINDS.iter().map(|kind| kind.name()).collect();
+    let learner_names: Vec<&str> = LEARNER_KINDS.iter().map(|kind| kind.name()).collect();
+    let teacher_spec = RadioSpec {
+        x: sidebar[2].top_left.x,
+        y: sidebar[2].top_left.y,
+        options: &teacher_names,
+        id: widget_id(&["spectator", "teacher"]),
+    };
+    let learner_spec = RadioSpec {
+        x: sidebar[5].top_left.x,
+        y: sidebar[5].top_left.y,
+        options: &learner_names,
+        id: widget_id(&["spectator", "learner"]),
+    };
+
+    let mut teacher_index = TEACHER_KINDS
+        .iter()
+        .position(|&kind| kind == state.teacher.kind)
+        .unwrap_or(0);
+    let mut learner_index = LEARNER_KINDS
+        .iter()
+        .position(|&kind| kind == state.learner_kind)
+        .unwrap_or(0);
+
+    //either choice starts the same puzzle over with the new pairing
+    let teacher_changed = radio_group(platform,
+                                      &mut state.ui_context,
+                                      &teacher_spec,
+                                      &mut teacher_index,
+                                      &input);
+    let learner_changed = radio_group(platform,
+                                      &mut state.ui_context,
+                                      &learner_spec,
+                                      &mut learner_index,
+                                      &input);
+
+    if teacher_changed || learner_changed {
+        state.teacher = Teacher::new(TEACHER_KINDS[teacher_index]);
+        state.learner_kind = LEARNER_KINDS[learner_index];
+        state.learner = new_learner(state.learner_kind);
+        state.examples.clear();
+        state.guessed_regex = None;
+        state.disagreement = None;
+        state.turn = InProgress;
+        state.message = None;
+    }
+
+    label(platform, sidebar[1], "teacher:", Anchor::Left);
+    label(platform, sidebar[4], "learner:", Anchor::Left);
+
+    print_line(platform, layout.regex, 0, unedged(state.regex.as_str()));
+    print_line(platform, layout.guess, 0, guess_text(&state.guessed_regex));
+
+    if let Some(ref text) = state.disagreement {
+        print_line(platform,
+                   layout.notes,
+                   0,
+                   &format!("where they still disagree: {}",
+                            if text.is_empty() { "ε" } else { text }));
+    }
+
+    let wrong = guess_misclassified(state);
+
+    if !wrong.is_empty() {
+        print_line(platform,
+                   layout.notes,
+                   1,
+                   &format!("the guess gets {} of the examples wrong (marked ≠)",
+                            wrong.len()));
+    }
+
+    if let Some(ref message) = state.message {
+        print_line(platform, layout.turn, 0, message);
+    } else if state.turn == Finished {
+        print_line(platform,
+                   layout.turn,
+                   0,
+                   &format!("The learner figured it out with {} examples!",
+                            state.examples.len()));
+    }
+
+    example_list(platform,
+                 layout.examples,
+                 &mut state.example_scroll,
+                 &state.examples,
+                 &wrong,
+                 &input);
+
+    false
+}
+
+const GAVE_UP_MESSAGE: &str = "The guess got too big to write out, so the learner gave up";
+
+//the teacher shows one example, or answers one of the learner's questions
+fn spectator_step(state: &mut State) {
+    let text = match state.learner.question() {
+        Some(Question::IsMember(text)) => Some(text),
+        None if state.learner.gave_up() => {
+            update_guess(state);
+            state.message = Some(GAVE_UP_MESSAGE.to_string());
+            state.turn = Finished;
+            state.last_step = None;
+            return;
+        }
+        Some(Question::IsEquivalent) |
+        None => {
+            if !state.examples.is_empty() {
+                update_guess(state);
+            }
+
+            if state.turn == Finished {
+                None
+            } else {
+                state.teacher.next_example(&state.regex,
+                                           &state.examples,
+                                           state.guessed_regex.as_ref())
+            }
+        }
+    };
+
+    match text {
+        Some(text) => {
+            if let Some(e) = state.examples.iter().find(|e| e.text == text) {
+                state.message = Some(format!("The learner is stuck, it still gets {} wrong",
+                                             if e.text.is_empty() { "ε" } else { &e.text }));
+                state.turn = Finished;
+                state.last_step = None;
+                return;
+            }
+
+            state.examples.push(Example::new(&text, &state.regex));
+            state.learner.observe(state.examples.last().unwrap());
+
+            update_guess(state);
+        }
+        None => {
+            //the teacher only runs out before the guess is right if the
+            //learner keeps getting something it was already shown wrong
+            if state.turn != Finished {
+                state.message = Some("The learner is stuck, the teacher has nothing else to show it"
+                                         .to_string());
+            }
+
+            state.turn = Finished;
+            state.last_step = None;
+        }
+    }
+}
+
+//lists the campaign's levels, letting the player start any they've unlocked
+fn campaign_update_and_render(platform: &Platform,
+                              state: &mut State,
+                              events: &mut Vec<Event>)
+                              -> bool {
+    use common::Constraint::*;
+
+    let input = gather_input(platform, state, events);
+    if input.quit {
+        return true;
+    }
+
+    state.ui_context.frame_init();
+
+    //the title, then the levels with the buttons beside them, then anything
+    //there is to say
+    let list_h = LEVELS.len() as i32 + 2;
+    let area = anchored(padded(screen((platform.size)()), 1),
+                        Size::new(45, list_h + 4),
+                        Anchor::Top);
+    let parts = column(area, &[Length(1), Length(list_h), Length(1)], 1);
+    let columns = row(parts[1], &[Length(30), Fill(1)], 4);
+    let buttons = column(columns[1], &[Length(BUTTON_H), Length(BUTTON_H)], 1);
+
+    label(platform, parts[0], "Campaign", Anchor::Left);
+
+    let finished = state.levels_finished;
+
+    let items: Vec<String> = LEVELS
+        .iter()
+        .enumerate()
+        .map(|(index, level)| {
+                 format!("{}. {}{}",
+                         index + 1,
+                         level.name,
+                         if index < finished {
+                             " ✓"
+                         } else if index > finished {
+                             " (locked)"
+                         } else {
+                             ""
+                         })
+             })
+        .collect();
+
+    let list_spec = ListBoxSpec {
+        x: columns[0].top_left.x,
+        y: columns[0].top_left.y,
+        w: columns[0].size.width,
+        h: columns[0].size.height,
+        items: &items,
+        id: widget_id(&["campaign", "levels"]),
+    };
+
+    if list_box(platform,
+                &mut state.ui_context,
+                &list_spec,
+                &mut state.campaign_choice,
+                &input)
+               .is_some() {
+        state.message = None;
+    }
+
+    let play_spec = button_in(buttons[0], "Play", widget_id(&["campaign", "play"]));
+
+    if do_button(platform,
+                 &mut state.ui_context,
+                 &play_spec,
+                 input.left_mouse_pressed,
+                 input.left_mouse_released) {
+        let level = state.campaign_choice;
+
+        if level > finished {
+            state.message = Some(format!("Finish level {} to unlock that one", finished + 1));
+        } else {
+            start_level(platform, state, level);
+            return false;
+        }
+    }
+
+    let back_spec = button_in(buttons[1], "Back", widget_id(&["campaign", "back"]));
+
+    if do_button(platform,
+                 &mut state.ui_context,
+                 &back_spec,
+                 input.left_mouse_pressed,
+                 input.left_mouse_released) {
+        state.campaign_screen = false;
+        state.title_screen = true;
+    }
+
+    if let Some(ref message) = state.message {
+        label(platform, parts[2], message, Anchor::Left);
+    }
+
+    false
+}
+
+fn start_level(platform: &Platform, state: &mut State, level: usize) {
+    let learner_kind = state.learner_kind;
+
+    *state = make_state((platform.size)(), false, state.rng, Mode::Master, learner_kind);
+    state.regex = generate_regex_with(&mut state.rng, &LEVELS[level].grammar);
+    state.level = Some(level);
+}
+
+//lets the player type in a code someone gave them and play that puzzle
+fn code_update_and_render(platform: &Platform,
+                          state: &mut State,
+                          events: &mut Vec<Event>)
+                          -> bool {
+    use common::Constraint::*;
+
+    let input = gather_input(platform, state, events);
+    if input.quit {
+        return true;
+    }
+
+    state.ui_context.frame_init();
+
+    //the prompt, anything wrong with the code, the code, then the buttons
+    let area = anchored(padded(screen((platform.size)()), 1),
+                        Size::new(55, 16),
+                        Anchor::Center);
+    let parts = column(area,
+                       &[Length(1), Length(1), Length(1), Fill(1), Length(BUTTON_H)],
+                       2);
+    let buttons = row(parts[4], &[Length(17), Length(17), Length(11)], 3);
+
+    let text_spec = TextFieldSpec {
+        x: parts[2].top_left.x,
+        y: parts[2].top_left.y,
+        allowed: is_code_char,
+        id: text_field_id(),
+    };
+
+    text_field(platform,
+               &mut state.ui_context,
+               &text_spec,
+               &mut state.text,
+               &input);
+
+    let master_spec = button_in(buttons[0], "Be the master", widget_id(&["code", "master"]));
+    let guesser_spec = button_in(buttons[1], "Guess it", widget_id(&["code", "guesser"]));
+    let cancel_spec = button_in(buttons[2], "Cancel", widget_id(&["code", "cancel"]));
+
+    let mode = if do_button(platform,
+                            &mut state.ui_context,
+                            &master_spec,
+                            input.left_mouse_pressed,
+                            input.left_mouse_released) {
+        Some(Mode::Master)
+    } else if do_button(platform,
+                        &mut state.ui_context,
+                        &guesser_spec,
+                        input.left_mouse_pressed,
+                        input.left_mouse_released) {
+        Some(Mode::Guesser)
+    } else {
+        None
+    };
+
+    if let Some(mode) = mode {
+        let decoded = PuzzleCode::decode(state.text.as_str())
+            .and_then(|code| code.target().map(|regex| (code, regex)));
+
+        match decoded {
+            Ok((code, regex)) => {
+                let learner_kind = state.learner_kind;
+                *state = make_state((platform.size)(), false, state.rng, mode, learner_kind);
+                state.regex = regex;
+
+                for text in code.examples.iter() {
+                    state.examples.push(Example::new(text, &state.regex));
+
+                    if mode == Mode::Master {
+                        state.learner.observe(state.examples.last().unwrap());
+                    }
+                }
+
+                if mode == Mode::Master && !state.examples.is_empty() {
+                    update_guess(state);
+                }
+            }
+            Err(error) => {
+                state.message = Some(format!("Couldn't use that code: {}", error));
+            }
+        }
+    }
+
+    if do_button(platform,
+                 &mut state.ui_context,
+                 &cancel_spec,
+                 input.left_mouse_pressed,
+                 input.left_mouse_released) {
+        state.code_screen = false;
+        state.title_screen = true;
+        state.text.clear();
+        state.message = None;
+    }
+
+    print_line(platform, parts[0], 0, "Type in a puzzle code");
+
+    if let Some(ref message) = state.message {
+        print_line(platform, parts[1], 0, message);
+    }
+
+
+    false
+}
+
+//the code for the current puzzle and the examples so far, or why there isn't
+//one
+fn puzzle_code(state: &State) -> String {
+    PuzzleCode {
+            regex: Some(unedged(state.regex.as_str()).to_string()),
+            seed: None,
+            examples: state.examples.iter().map(|e| e.text.clone()).collect(),
+        }
+        .encode()
+        .unwrap_or_else(|error| error.to_string())
+}
+
+pub fn labelling_update_and_render(platform: &Platform,
+                                   state: &mut State,
+                                   events: &mut Vec<Event>)
+                                   -> bool {
+    use common::Constraint::*;
+
+    let input = gather_input(platform, state, events);
+    if input.quit {
+        return true;
+    }
+
+    state.ui_context.frame_init();
+
+    let layout = game_layout((platform.size)(), REGEX_KEYBOARD_H);
+
+    regex_keyboard(platform, state, &layout, &input);
+
+    let actions = row(layout.actions, &[Length(11), Length(17), Length(14)], 1);
+    let matches_spec = button_in(actions[0], "Matches", widget_id(&["labelling", "matches"]));
+    let non_matches_spec = button_in(actions[1],
+                                     "Doesn't match",
+                                     widget_id(&["labelling", "non-matches"]));
+    let check_spec = button_in(actions[2], "Check rule", widget_id(&["labelling", "check"]));
+
+    let label = if do_button(platform,
+                             &mut state.ui_context,
+                             &matches_spec,
+                             input.left_mouse_pressed,
+                             input.left_mouse_released) {
+        Some(true)
+    } else if do_button(platform,
+                        &mut state.ui_context,
+                        &non_matches_spec,
+                        input.left_mouse_pressed,
+                        input.left_mouse_released) {
+        Some(false)
+    } else {
+        None
+    };
+
+    if let Some(matched) = label {
+        if !state.text.as_str().chars().all(|c| ALPHABET.contains(&c)) {
+            state.message = Some("Only the digits 0 to 3 can be labelled".to_string());
+        } else if state.examples.iter().any(|e| e.text == state.text.as_str()) {
+            state.message = Some("That one is already labelled".to_string());
+        } else {
+            state.examples.push(Example {
+                                    text: state.text.as_str().to_string(),
+                                    matched,
+                                });
+            state.learner.observe(state.examples.last().unwrap());
+            state.guessed_regex = state.learner.hypothesis();
+
+            state.message = None;
+            state.text.clear();
+        }
+    }
+
+    if do_button(platform,
+                 &mut state.ui_context,
+                 &check_spec,
+                 input.left_mouse_pressed,
+                 input.left_mouse_released) {
+        check_rule(state);
+    }
+
+    let learner_spec = button_in(column(layout.sidebar, &[Fill(1), Length(BUTTON_H)], 0)[1],
+                                 state.learner_kind.name(),
+                                 widget_id(&["labelling", "learner"]));
+
+    if do_button(platform,
+                 &mut state.ui_context,
+                 &learner_spec,
+                 input.left_mouse_pressed,
+                 input.left_mouse_released) {
+        state.learner_kind = state.learner_kind.next();
+        if state.learner_kind.is_active() {
+            state.learner_kind = state.learner_kind.next();
+        }
+
+        state.learner = new_learner(state.learner_kind);
+        for e in state.examples.iter() {
+            state.learner.observe(e);
+        }
+        state.guessed_regex = state.learner.hypothesis();
+    }
+
+    print_line(platform, layout.regex, 0, "the rule is in your head");
+    print_line(platform, layout.guess, 0, guess_text(&state.guessed_regex));
+
+    if let Some(ref message) = state.message {
+        print_line(platform, layout.notes, 0, message);
+    }
+
+
+    let wrong = guess_misclassified(state);
+
+    example_list(platform,
+                 layout.examples,
+                 &mut state.example_scroll,
+                 &state.examples,
+                 &wrong,
+                 &input);
+
+    if state.turn == Finished {
+        let new_spec = button_in(new_puzzle_rect(&layout),
+                                 "New Puzzle",
+                                 widget_id(&["game", "new puzzle"]));
+
+        if do_button(platform,
+                     &mut state.ui_context,
+                     &new_spec,
+                     input.left_mouse_pressed,
+                     input.left_mouse_released) {
+            *state = make_state((platform.size)(),
+                                false,
+                                state.rng,
+                                state.mode,
+                                state.learner_kind);
+        }
+    }
+
+    false
+}
+
+//the master typed out the rule they had in mind, so see if it fits the labels
+//they gave, and if the computer got it
+fn check_rule(state: &mut State) {
+    let rule = match parse_typed_regex(state.text.as_str()) {
+        Ok(rule) => rule,
+        Err(error) => {
+            state.message = Some(format!("That isn't a regex: {}", error));
+            return;
+        }
+    };
+
+    let wrong: Vec<&str> = misclassified(&rule, &state.examples)
+        .into_iter()
+        .map(|i| if state.examples[i].text.is_empty() {
+                 "ε"
+             } else {
+                 &state.examples[i].text
+             })
+        .collect();
+
+    let rule_str = unedged(rule.as_str());
+    let figured_out = state.guessed_regex
+        .as_ref()
+        .map(|guess| regexes_equivalent(&rule, guess))
+        .unwrap_or(false);
+
+    state.message = Some(if !wrong.is_empty() {
+                             format!("{} doesn't fit your labels for {}",
+                                     rule_str,
+                                     wrong.join(", "))
+                         } else if figured_out {
+                             state.turn = Finished;
+                             format!("{} fits your labels, and they figured it out!", rule_str)
+                         } else {
+                             format!("{} fits your labels, but they haven't figured it out yet",
+                                     rule_str)
+                         });
+
+    state.text.clear();
+}
+
+//the text field, and the digit, regex symbol and backspace buttons, which
+//all edit `state.text`
+fn regex_keyboard(platform: &Platform, state: &mut State, layout: &GameLayout, input: &Input) {
+    use common::Constraint::*;
+
+    text_field(platform,
+               &mut state.ui_context,
+               &entry_spec(layout, is_regex_char),
+               &mut state.text,
+               input);
+
+    let rows = column(layout.keyboard,
+                      &[Length(BUTTON_H), Length(BUTTON_H), Length(BUTTON_H)],
+                      0);
+    let keys = row(rows[0],
+                   &[Length(5), Length(5), Length(5), Length(5), Length(7)],
+                   1);
+
+    for (i, &key) in keys.iter().take(4).enumerate() {
+        let digit = i.to_string();
+        let spec = button_in(key, &digit, widget_id(&["keyboard", &digit]));
+
+        if do_button(platform,
+                     &mut state.ui_context,
+                     &spec,
+                     input.left_mouse_pressed,
+                     input.left_mouse_released) {
+            state.text.insert_str(&digit);
+        }
+    }
+
+    //the symbols don't fit on one row next to the example list
+    let symbol_keys: Vec<Rect> = row(rows[1], &[Length(5); 5], 1)
+        .into_iter()
+        .chain(row(rows[2], &[Length(5); 4], 1))
+        .collect();
+
+    for (index, &symbol) in REGEX_SYMBOLS.iter().enumerate() {
+        let text = symbol.to_string();
+        let spec = button_in(symbol_keys[index], &text, widget_id(&["keyboard", &text]));
+
+        if do_button(platform,
+                     &mut state.ui_context,
+                     &spec,
+                     input.left_mouse_pressed,
+                     input.left_mouse_released) {
+            state.text.insert(symbol);
+        }
+    }
+
+    let backspace_spec = button_in(keys[4], "⌫", widget_id(&["keyboard", "backspace"]));
+
+    if do_button(platform,
+                 &mut state.ui_context,
+                 &backspace_spec,
+                 input.left_mouse_pressed,
+                 input.left_mouse_released) {
+        state.text.backspace();
+    }
+}
+
+//checks the player's guess, which is in `state.text`, against the hidden regex
+fn judge_guess(state: &mut State) {
+    let guess = match parse_typed_regex(state.text.as_str()) {
+        Ok(guess) => guess,
+        Err(error) => {
+            state.message = Some(format!("That isn't a regex: {}", error));
+            return;
+        }
+    };
+
+    if regexes_equivalent(&state.regex, &guess) {
+        state.turn = Finished;
+        state.message = Some(if state.mode == Mode::Race {
+                                 "You beat the computer!".to_string()
+                             } else {
+                                 "You figured it out!".to_string()
+                             });
+    } else if let Some(text) = shortest_counterexample(&state.regex, &guess) {
+        let example = Example::new(&text, &state.regex);
+
+        state.message = Some(format!("Not quite, {} {}",
+                                     if text.is_empty() { "ε" } else { &text },
+                                     if example.matched {
+                                         "matches but your guess doesn't"
+                                     } else {
+                                         "doesn't match but your guess does"
+                                     }));
+
+        add_shared_example(state, example);
+    }
+
+    state.guessed_regex = Some(guess);
+    state.text.clear();
+}
+
+//the guess as it's shown, which is blank until there is one
+fn guess_text(guess: &Option<Regex>) -> &str {
+    guess.as_ref().map(|regex| unedged(regex.as_str())).unwrap_or("")
+}
+
+//the examples the current guess gets wrong, which is none of them until there
+//is a guess
+fn guess_misclassified(state: &State) -> Vec<usize> {
+    state.guessed_regex
+        .as_ref()
+        .map(|regex| misclassified(regex, &state.examples))
+        .unwrap_or_default()
+}
+
+fn parse_typed_regex(text: &str) -> Result<Regex, String> {
+    RegexAst::parse(text)
+        .map_err(|e| e.to_string())
+        .and_then(|ast| edged_regex(&ast.to_string()).map_err(|e| e.to_string()))
+}
+
+//in a race the computer sees every example the player does, and guesses as
+//soon as it has seen it
+fn add_shared_example(state: &mut State, example: Example) {
+    if state.examples.iter().any(|e| e.text == example.text) {
+        return;
+    }
+
+    state.examples.push(example);
+
+    if state.mode == Mode::Race && state.turn == InProgress {
+        state.learner.observe(state.examples.last().unwrap());
+
+        if state.learner
+               .hypothesis()
+               .map(|guess| regexes_equivalent(&state.regex, &guess))
+               .unwrap_or(false) {
+            state.turn = Finished;
+            state.message = Some("The computer figured it out first!".to_string());
+        }
+    }
+}
+
+//everything a frame needs to know about the events since the last one
+#[derive(Default)]
+struct Input {
+    left_mouse_pressed: bool,
+    left_mouse_released: bool,
+    enter_key: bool,
+    //typing and caret movement, in the order it happened
+    edits: Vec<Edit>,
+    //mouse wheel steps, positive for down
+    scroll: i32,
+    page_up: bool,
+    page_down: bool,
+    up: bool,
+    down: bool,
+    //any key other than the mouse buttons and the ones that move or use the
+    //focus
+    any_key: bool,
+    quit: bool,
+}
+
+fn gather_input(platform: &Platform, state: &mut State, events: &mut Vec<Event>) -> Input {
+    let mut input = Input::default();
+    state.ui_context.focus_pressed = false;
+
+    for event in events {
+        cross_mode_event_handling(platform, state, event);
+
+        match *event {
+            Event::KeyPressed {
+                key: KeyCode::MouseLeft,
+                ctrl: _,
+                shift: _,
+            } => {
+                input.left_mouse_pressed = true;
+            }
+            Event::KeyReleased {
+                key: KeyCode::MouseLeft,
+                ctrl: _,
+                shift: _,
+            } => {
+                input.left_mouse_released = true;
+            }
+            Event::Close |
+            Event::KeyPressed {
+                key: KeyCode::Escape,
+                ctrl: _,
+                shift: _,
+            } => input.quit = true,
+            Event::KeyReleased {
+                key: KeyCode::Enter,
+                ctrl: _,
+                shift: _,
+            } => {
+                if state.ui_context.has_focus() {
+                    state.ui_context.focus_pressed = true;
+                } else {
+                    input.enter_key = true;
+                }
+            }
+            Event::KeyPressed {
+                key: KeyCode::Tab,
+                ctrl: _,
+                shift,
+            } => {
+                if shift {
+                    state.ui_context.focus_previous();
+                } else {
+                    state.ui_context.focus_next();
+                }
+            }
+            Event::KeyPressed {
+                key: KeyCode::Space,
+                ctrl: _,
+                shift: _,
+            } if state.ui_context.has_focus() => {
+                state.ui_context.focus_pressed = true;
+            }
+            Event::KeyPressed {
+                key: KeyCode::Up,
+                ctrl: _,
+                shift: _,
+            } => {
+                input.up = true;
+            }
+            Event::KeyPressed {
+                key: KeyCode::Down,
+                ctrl: _,
+                shift: _,
+            } => {
+                input.down = true;
+            }
+            Event::MouseScroll { delta } => {
+                input.scroll += delta;
+            }
+            Event::KeyPressed {
+                key: KeyCode::PageUp,
+                ctrl: _,
+                shift: _,
+            } => {
+                input.page_up = true;
+            }
+            Event::KeyPressed {
+                key: KeyCode::PageDown,
+                ctrl: _,
+                shift: _,
+            } => {
+                input.page_down = true;
+            }
+            _ => (),
+        }
+
+        if let Event::KeyPressed { key, ctrl, shift } = *event {
+            let focus_key = key == KeyCode::Tab ||
+                            (state.ui_context.has_focus() &&
+                             (key == KeyCode::Space || key == KeyCode::Enter));
+
+            if key != KeyCode::MouseLeft && key != KeyCode::MouseRight && !focus_key {
+                input.any_key = true;
+            }
+
+            if !ctrl {
+                if let Some(edit) = edit(key, shift) {
+                    input.edits.push(edit);
+                }
+            }
+        }
+    }
+
+    input
+}
+
+const LETTER_KEYS: [KeyCode; 26] = [KeyCode::A, KeyCode::B, KeyCode::C, KeyCode::D, KeyCode::E,
+                                    KeyCode::F, KeyCode::G, KeyCode::H, KeyCode::I, KeyCode::J,
+                                    KeyCode::K, KeyCode::L, KeyCode::M, KeyCode::N, KeyCode::O,
+                                    KeyCode::P, KeyCode::Q, KeyCode::R, KeyCode::S, KeyCode::T,
+                                    KeyCode::U, KeyCode::V, KeyCode::W, KeyCode::X, KeyCode::Y,
+                                    KeyCode::Z];
+const DIGIT_KEYS: [KeyCode; 10] = [KeyCode::Row0, KeyCode::Row1, KeyCode::Row2, KeyCode::Row3,
+                                   KeyCode::Row4, KeyCode::Row5, KeyCode::Row6, KeyCode::Row7,
+                                   KeyCode::Row8, KeyCode::Row9];
+const NUMPAD_KEYS: [KeyCode; 10] = [KeyCode::Num0, KeyCode::Num1, KeyCode::Num2, KeyCode::Num3,
+                                    KeyCode::Num4, KeyCode::Num5, KeyCode::Num6, KeyCode::Num7,
+                                    KeyCode::Num8, KeyCode::Num9];
+
+//a change to a `TextField`. The `bool`s say whether shift was held, which
+//selects the text the caret moves over.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Edit {
+    Insert(char),
+    Backspace,
+    Delete,
+    Left(bool),
+    Right(bool),
+    Home(bool),
+    End(bool),
+}
+
+fn edit(key: KeyCode, shift: bool) -> Option<Edit> {
+    match key {
+        KeyCode::Backspace => Some(Edit::Backspace),
+        KeyCode::Delete => Some(Edit::Delete),
+        KeyCode::Left => Some(Edit::Left(shift)),
+        KeyCode::Right => Some(Edit::Right(shift)),
+        KeyCode::Home => Some(Edit::Home(shift)),
+        KeyCode::End => Some(Edit::End(shift)),
+        _ => {
+            code_char(key, shift)
+                .or_else(|| regex_symbol(key, shift))
+                .map(Edit::Insert)
+        }
+    }
+}
+
+//letters, digits, `-` and `_`, which is everything a puzzle code uses
+fn code_char(key: KeyCode, shift: bool) -> Option<char> {
+    if let Some(i) = LETTER_KEYS.iter().position(|&k| k == key) {
+        let c = (b'a' + i as u8) as char;
+
+        return Some(if shift { c.to_ascii_uppercase() } else { c });
+    }
+
+    if let Some(i) = DIGIT_KEYS.iter().position(|&k| k == key) {
+        //shift-0 is `)` and so on
+        return if shift {
+                   None
+               } else {
+                   Some((b'0' + i as u8) as char)
+               };
+    }
+
+    if let Some(i) = NUMPAD_KEYS.iter().position(|&k| k == key) {
+        return Some((b'0' + i as u8) as char);
+    }
+
+    match (key, shift) {
+        (KeyCode::Minus, false) => Some('-'),
+        (KeyCode::Minus, true) => Some('_'),
+        _ => None,
+    }
+}
+
+fn regex_symbol(key: KeyCode, shift: bool) -> Option<char> {
+    match (key, shift) {
+        (KeyCode::LeftBracket, false) => Some('['),
+        (KeyCode::RightBracket, false) => Some(']'),
+        (KeyCode::Row9, true) => Some('('),
+        (KeyCode::Row0, true) => Some(')'),
+        (KeyCode::Period, false) |
+        (KeyCode::NumPeriod, _) => Some('.'),
+        (KeyCode::Backslash, true) => Some('|'),
+        (KeyCode::Row8, true) |
+        (KeyCode::NumMultiply, _) => Some('*'),
+        (KeyCode::Equals, true) |
+        (KeyCode::NumPlus, _) => Some('+'),
+        (KeyCode::Slash, true) => Some('?'),
+        _ => None,
+    }
+}
+
+fn update_guess(state: &mut State) {
+    let regex = state.learner.hypothesis();
+    let right = regex
+        .as_ref()
+        .map(|regex| regexes_equivalent(&state.regex, regex))
+        .unwrap_or(false);
+
+    if right && state.turn != Finished {
+        state.turn = Finished;
+
+        if let Some(day) = state.daily {
+            record_daily_result(state, day);
+        }
+        if let Some(level) = state.level {
+            state.message = Some(match save_progress(Path::new(PROGRESS_FILE), level) {
+                                     Ok(_) => format!("level {} done!", level + 1),
+                                     Err(error) => {
+                                         format!("couldn't save the progress: {}", error)
+                                     }
+                                 });
+        }
+    } else if state.mode == Mode::Challenge && state.turn == InProgress &&
+              state.examples.len() >= EXAMPLE_BUDGET {
+        state.turn = OutOfExamples;
+    }
+    state.disagreement = regex
+        .as_ref()
+        .and_then(|regex| shortest_counterexample(&state.regex, regex));
+
+    state.guessed_regex = regex;
+}
+
+fn record_daily_result(state: &mut State, day: u64) {
+    let result = DailyResult {
+        date: date_string(day),
+        learner: state.learner_kind.name().to_string(),
+        examples: state.examples.len(),
+    };
+
+    state.message = Some(match record_result(Path::new(RESULTS_FILE), result.clone()) {
+                             Ok(ref counted) if *counted == result => {
+                                 format!("recorded: {} examples with {}",
+                                         counted.examples,
+                                         counted.learner)
+                             }
+                             Ok(counted) => {
+                                 format!("your first try took {} examples with {}",
+                                         counted.examples,
+                                         counted.learner)
+                             }
+                             Err(error) => format!("couldn't record the result: {}", error),
+                         });
+}
+
+fn cross_mode_event_handling(platform: &Platform, state: &mut State, event: &Event) {
+    match *event {
+        Event::KeyPressed {
+            key: KeyCode::R,
+            ctrl: true,
+            shift: _,
+        } => {
+            println!("reset");
+            *state = new_state((platform.size)());
+        }
+        //the title screen is skipped in debug builds, so this is the only way
+        //to get to the other modes there
+        Event::KeyPressed {
+            key: KeyCode::T,
+            ctrl: true,
+            shift: _,
+        } => {
+            state.title_screen = true;
+        }
+        _ => (),
+    }
+}
+
+const SIDEBAR_W: i32 = 17;
+const EXAMPLE_LIST_W: i32 = 26;
+//just the digits and backspace, or the regex symbols as well
+const DIGIT_KEYBOARD_H: i32 = BUTTON_H;
+const REGEX_KEYBOARD_H: i32 = 3 * BUTTON_H;
+
+//Where everything goes on the screens that play a puzzle. It's worked out
+//from the window's size every frame, so resizing the window moves things.
+struct GameLayout {
+    //buttons down the left, stacked up from the bottom
+    sidebar: Rect,
+    //which puzzle this is, when there's something to say about that
+    status: Rect,
+    regex: Rect,
+    guess: Rect,
+    //two lines for anything else there is to say
+    notes: Rect,
+    //the current example's mark, then the text field
+    entry: Rect,
+    //questions, answers and results
+    turn: Rect,
+    keyboard: Rect,
+    //the main buttons, along the bottom
+    actions: Rect,
+    examples: Rect,
+}
+
+fn game_layout(size: Size, keyboard_h: i32) -> GameLayout {
+    use common::Constraint::*;
+
+    let parts = column(padded(screen(size), 1), &[Fill(1), Length(BUTTON_H)], 1);
+    let columns = row(parts[0],
+                      &[Length(SIDEBAR_W), Fill(1), Length(EXAMPLE_LIST_W)],
+                      2);
+    let lines = column(columns[1],
+                       &[Length(1),
+                         Length(1),
+                         Length(1),
+                         Length(2),
+                         Length(1),
+                         Fill(1),
+                         Length(keyboard_h)],
+                       1);
+
+    GameLayout {
+        sidebar: columns[0],
+        status: lines[0],
+        regex: lines[1],
+        guess: lines[2],
+        notes: lines[3],
+        entry: lines[4],
+        turn: lines[5],
+        keyboard: lines[6],
+        actions: parts[1],
+        examples: columns[2],
+    }
+}
+
+//the text field on the game screens, after the example's mark if there is one
+fn entry_spec(layout: &GameLayout, allowed: fn(char) -> bool) -> TextFieldSpec {
+    TextFieldSpec {
+        x: layout.entry.top_left.x + 3,
+        y: layout.entry.top_left.y,
+        allowed,
+        id: text_field_id(),
+    }
+}
+
+//where the New Puzzle button goes once a puzzle is over
+fn new_puzzle_rect(layout: &GameLayout) -> Rect {
+    anchored(layout.actions, Size::new(14, BUTTON_H), Anchor::Right)
+}
+
+//Shows as many of the examples as fit in `rect`, two rows each, marking the
+//ones in `wrong` with ≠. The mouse wheel scrolls it while the mouse is over
+//it, and Page Up and Page Down scroll it from anywhere. New examples scroll it
+//back down so they can be seen.
+fn example_list(platform: &Platform,
+                rect: Rect,
+                scroll: &mut ScrollPosition,
+                examples: &[Example],
+                wrong: &[usize],
+                input: &Input) {
+    let (x, y, w, h) = (rect.top_left.x, rect.top_left.y, rect.size.width, rect.size.height);
+    let visible = (h / 2) as usize;
+    let len = examples.len();
+    let max_scroll = len.saturating_sub(visible);
+
+    if len != scroll.len {
+        scroll.from_bottom = 0;
+        scroll.len = len;
+    }
+
+    let mut from_bottom = scroll.from_bottom as i32;
+    if inside_rect((platform.mouse_position)(), x, y, w, h) {
+        from_bottom -= input.scroll;
+    }
+    if input.page_up {
+        from_bottom += visible as i32;
+    }
+    if input.page_down {
+        from_bottom -= visible as i32;
+    }
+    scroll.from_bottom = (from_bottom.max(0) as usize).min(max_scroll);
+
+    let first = max_scroll - scroll.from_bottom;
+
+    for (row, index) in (first..len.min(first + visible)).enumerate() {
+        let row_y = y + 2 * row as i32;
+
+        examples[index].print_xy(platform, x + 2, row_y);
+
+        if wrong.contains(&index) {
+            (platform.print_xy)(x, row_y, "≠");
+        }
+    }
+
+    let thumb_h = (h as usize * visible / len.max(1)).max(1) as i32;
+
+    //there's only a scrollbar when there's something to scroll
+    if let Some(offset) = ((h - thumb_h) as usize * first).checked_div(max_scroll) {
+        let bar_x = x + w - 1;
+        let thumb_y = y + offset as i32;
+
+        for row_y in y..y + h {
+            (platform.print_xy)(bar_x,
+                                row_y,
+                                if thumb_y <= row_y && row_y < thumb_y + thumb_h {
+                                    "█"
+                                } else {
+                                    "│"
+                                });
+        }
+    }
+}
+
+//every screen has at most one text field, and it's always `state.text`
+fn text_field_id() -> UiId {
+    widget_id(&["text field"])
+}
+
+fn is_example_char(c: char) -> bool {
+    ALPHABET.contains(&c)
+}
+
+fn is_regex_char(c: char) -> bool {
+    ALPHABET.contains(&c) || REGEX_SYMBOLS.contains(&c)
+}
+
+fn is_code_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '-' || c == '_'
 }
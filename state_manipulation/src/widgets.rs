@@ -0,0 +1,584 @@
+//The immediate mode widgets the screens are made of. Each one is a function
+//called every frame, which draws the widget and reports what happened to it.
+//`UIContext` remembers which one the mouse is over, which one is being
+//pressed and which one has the keyboard focus, by id. The ids come from
+//`widget_id` and `child_id`.
+
+use common::*;
+
+use {Edit, Input};
+
+pub const BUTTON_H: i32 = 3;
+
+const CARET_COLOUR: Color = Color {
+    red: 160,
+    green: 160,
+    blue: 160,
+    alpha: 255,
+};
+const SELECTION_COLOUR: Color = Color {
+    red: 40,
+    green: 80,
+    blue: 160,
+    alpha: 255,
+};
+//the text of the widget the mouse is over, for the ones without a border
+const HOT_COLOUR: Color = Color {
+    red: 255,
+    green: 220,
+    blue: 120,
+    alpha: 255,
+};
+//behind the text of the focused widget, likewise
+const FOCUS_COLOUR: Color = Color {
+    red: 90,
+    green: 90,
+    blue: 90,
+    alpha: 255,
+};
+
+const BORDER: [&str; 8] = ["┌", "─", "┐", "│", "│", "└", "─", "┘"];
+const HOT_BORDER: [&str; 8] = ["┌", "─", "╖", "│", "║", "╘", "═", "╝"];
+const PRESSED_BORDER: [&str; 8] = ["╔", "═", "╕", "║", "│", "╙", "─", "┘"];
+const FOCUS_BORDER: [&str; 8] = ["╒", "═", "╕", "│", "│", "╘", "═", "╛"];
+const DIALOG_BORDER: [&str; 8] = ["╔", "═", "╗", "║", "║", "╚", "═", "╝"];
+
+//Claims `id` and works out whether the widget in the given rect was clicked,
+//which is the mouse being pressed on it and then released on it, or Space or
+//Enter being pressed while it has the focus. While a dialog is open, only the
+//dialog's widgets can be clicked or focused.
+fn clicked(platform: &Platform,
+           context: &mut UIContext,
+           id: UiId,
+           rect: Rect,
+           left_mouse_pressed: bool,
+           left_mouse_released: bool)
+           -> bool {
+    context.claim(id);
+
+    if !context.interactive() {
+        return false;
+    }
+
+    context.add_focusable(id);
+
+    let mut result = context.focused == id && context.focus_pressed;
+
+    let mouse_pos = (platform.mouse_position)();
+    let inside = inside_rect(mouse_pos,
+                             rect.top_left.x,
+                             rect.top_left.y,
+                             rect.size.width,
+                             rect.size.height);
+
+    if context.active == id {
+        if left_mouse_released {
+            result = result || (context.hot == id && inside);
+
+            context.set_not_active();
+        }
+    } else if context.hot == id {
+        if left_mouse_pressed {
+            context.set_active(id);
+        }
+    }
+
+    if inside {
+        context.set_next_hot(id);
+    }
+
+    result
+}
+
+pub struct ButtonSpec {
+    pub x: i32,
+    pub y: i32,
+    pub w: i32,
+    pub h: i32,
+    pub text: String,
+    pub id: i32,
+}
+
+//a button that fills `rect`
+pub fn button_in(rect: Rect, text: &str, id: UiId) -> ButtonSpec {
+    ButtonSpec {
+        x: rect.top_left.x,
+        y: rect.top_left.y,
+        w: rect.size.width,
+        h: rect.size.height,
+        text: text.to_string(),
+        id,
+    }
+}
+
+//calling this once will swallow multiple clicks on the button. We could either
+//pass in and return the number of clicks to fix that, or this could simply be
+//called multiple times per frame (once for each click).
+pub fn do_button(platform: &Platform,
+                 context: &mut UIContext,
+                 spec: &ButtonSpec,
+                 left_mouse_pressed: bool,
+                 left_mouse_released: bool)
+                 -> bool {
+    let rect = Rect::from_values(spec.x, spec.y, spec.w, spec.h);
+    let result = clicked(platform,
+                         context,
+                         spec.id,
+                         rect,
+                         left_mouse_pressed,
+                         left_mouse_released);
+
+    let edges = if context.active == spec.id && left_mouse_pressed {
+        PRESSED_BORDER
+    } else if context.focused == spec.id {
+        FOCUS_BORDER
+    } else if context.hot == spec.id {
+        HOT_BORDER
+    } else {
+        BORDER
+    };
+
+    draw_rect_with(platform, spec.x, spec.y, spec.w, spec.h, edges);
+    print_centered_line(platform, spec.x, spec.y, spec.w, spec.h, &spec.text);
+
+    result
+}
+
+//A button that stays pressed in while `on` is set, and flips it when it's
+//clicked. Returns whether it was. The focus shows over being pressed in, so
+//the text should say which way it is too.
+pub fn toggle(platform: &Platform,
+              context: &mut UIContext,
+              spec: &ButtonSpec,
+              on: &mut bool,
+              input: &Input)
+              -> bool {
+    let rect = Rect::from_values(spec.x, spec.y, spec.w, spec.h);
+    let result = clicked(platform,
+                         context,
+                         spec.id,
+                         rect,
+                         input.left_mouse_pressed,
+                         input.left_mouse_released);
+
+    if result {
+        *on = !*on;
+    }
+
+    let edges = if context.focused == spec.id {
+        FOCUS_BORDER
+    } else if *on {
+        PRESSED_BORDER
+    } else if context.hot == spec.id {
+        HOT_BORDER
+    } else {
+        BORDER
+    };
+
+    draw_rect_with(platform, spec.x, spec.y, spec.w, spec.h, edges);
+    print_centered_line(platform, spec.x, spec.y, spec.w, spec.h, &spec.text);
+
+    result
+}
+
+pub struct CheckboxSpec {
+    pub x: i32,
+    pub y: i32,
+    pub text: String,
+    pub id: UiId,
+}
+
+//A box that's ticked while `checked` is set, with its text after it.
+//Clicking either flips it. Returns whether it was clicked.
+pub fn checkbox(platform: &Platform,
+                context: &mut UIContext,
+                spec: &CheckboxSpec,
+                checked: &mut bool,
+                input: &Input)
+                -> bool {
+    let w = 2 + spec.text.chars().count() as i32;
+    let result = clicked(platform,
+                         context,
+                         spec.id,
+                         Rect::from_values(spec.x, spec.y, w, 1),
+                         input.left_mouse_pressed,
+                         input.left_mouse_released);
+
+    if result {
+        *checked = !*checked;
+    }
+
+    //the glyphs are drawn from `checkbox.png`
+    let text = format!("{} {}", if *checked { "☑" } else { "☐" }, spec.text);
+    print_highlighted(platform, context, spec.id, spec.x, spec.y, &text);
+
+    result
+}
+
+pub struct RadioSpec<'a> {
+    pub x: i32,
+    pub y: i32,
+    //one line each, top to bottom
+    pub options: &'a [&'a str],
+    pub id: UiId,
+}
+
+//Options of which exactly one is chosen, the one at `selected`. Clicking one
+//chooses it. Returns whether the choice changed.
+pub fn radio_group(platform: &Platform,
+                   context: &mut UIContext,
+                   spec: &RadioSpec,
+                   selected: &mut usize,
+                   input: &Input)
+                   -> bool {
+    let mut result = false;
+
+    for (index, option) in spec.options.iter().enumerate() {
+        let id = child_id(spec.id, option);
+        let y = spec.y + index as i32;
+        let w = 4 + option.chars().count() as i32;
+
+        if clicked(platform,
+                   context,
+                   id,
+                   Rect::from_values(spec.x, y, w, 1),
+                   input.left_mouse_pressed,
+                   input.left_mouse_released) && *selected != index {
+            *selected = index;
+            result = true;
+        }
+
+        let text = format!("({}) {}", if *selected == index { "•" } else { " " }, option);
+        print_highlighted(platform, context, id, spec.x, y, &text);
+    }
+
+    result
+}
+
+//Text that isn't interacted with, on one line, in the `anchor` part of
+//`rect`. Anything that doesn't fit is cut off.
+pub fn label(platform: &Platform, rect: Rect, text: &str, anchor: Anchor) {
+    let len = text.chars().count() as i32;
+    let place = anchored(rect, Size::new(len, 1), anchor);
+    let shown: String = text.chars().take(place.size.width as usize).collect();
+
+    (platform.print_xy)(place.top_left.x, place.top_left.y, &shown);
+}
+
+pub struct ListBoxSpec<'a> {
+    pub x: i32,
+    pub y: i32,
+    pub w: i32,
+    pub h: i32,
+    pub items: &'a [String],
+    pub id: UiId,
+}
+
+//A bordered list of items, one line each, with the one at `selected`
+//highlighted and scrolled into view. Clicking an item selects it, and returns
+//its index. While it has the focus, Up and Down move the selection, and Space
+//or Enter return the selected item.
+pub fn list_box(platform: &Platform,
+                context: &mut UIContext,
+                spec: &ListBoxSpec,
+                selected: &mut usize,
+                input: &Input)
+                -> Option<usize> {
+    let ListBoxSpec { x, y, w, h, items, id } = *spec;
+    let visible = (h - 2).max(0) as usize;
+
+    if context.focused == id && context.interactive() {
+        if input.up {
+            *selected = selected.saturating_sub(1);
+        }
+        if input.down {
+            *selected = (*selected + 1).min(items.len().saturating_sub(1));
+        }
+    }
+
+    let first = (*selected + 1).saturating_sub(visible);
+    let by_keyboard = context.focused == id && context.focus_pressed;
+
+    let result = if !clicked(platform,
+                             context,
+                             id,
+                             Rect::from_values(x + 1, y + 1, (w - 2).max(0), visible as i32),
+                             input.left_mouse_pressed,
+                             input.left_mouse_released) {
+        None
+    } else if by_keyboard {
+        Some(*selected)
+    } else {
+        let row = ((platform.mouse_position)().y - (y + 1)).max(0) as usize;
+
+        Some(first + row).filter(|&index| index < items.len())
+    };
+
+    if let Some(index) = result {
+        *selected = index;
+    }
+
+    let edges = if context.focused == id {
+        FOCUS_BORDER
+    } else if context.hot == id {
+        HOT_BORDER
+    } else {
+        BORDER
+    };
+
+    draw_rect_with(platform, x, y, w, h, edges);
+
+    for (row, index) in (first..items.len().min(first + visible)).enumerate() {
+        let row_y = y + 1 + row as i32;
+        let text: String = items[index].chars().take((w - 4).max(0) as usize).collect();
+
+        if index == *selected {
+            let bg = (platform.get_background)();
+
+            (platform.set_background)(SELECTION_COLOUR);
+            for column in x + 1..x + w - 1 {
+                (platform.print_xy)(column, row_y, " ");
+            }
+            (platform.print_xy)(x + 2, row_y, &text);
+            (platform.set_background)(bg);
+        } else {
+            (platform.print_xy)(x + 2, row_y, &text);
+        }
+    }
+
+    result
+}
+
+pub struct DialogSpec<'a> {
+    pub lines: &'a [String],
+    //along the bottom, left to right
+    pub buttons: &'a [&'a str],
+    pub id: UiId,
+}
+
+//A box in the middle of the screen, over everything else, with some lines of
+//text and a row of buttons. Nothing else can be used while it's shown, so it
+//should be done after everything else on the screen. The first button gets
+//the focus, unless another one has it. Returns the index of the button that
+//was clicked, if one was.
+pub fn dialog(platform: &Platform,
+              context: &mut UIContext,
+              spec: &DialogSpec,
+              input: &Input)
+              -> Option<usize> {
+    use common::Constraint::*;
+
+    let button_widths: Vec<i32> = spec.buttons
+        .iter()
+        .map(|text| text.chars().count() as i32 + 4)
+        .collect();
+    let buttons_w = button_widths.iter().sum::<i32>() + button_widths.len() as i32 - 1;
+    let text_w = spec.lines
+        .iter()
+        .map(|line| line.chars().count() as i32)
+        .max()
+        .unwrap_or(0);
+    let lines_h = spec.lines.len() as i32;
+
+    //a border and a space all the way around
+    let size = Size::new(text_w.max(buttons_w) + 4, lines_h + BUTTON_H + 5);
+    let area = anchored(screen((platform.size)()), size, Anchor::Center);
+
+    draw_rect_with(platform,
+                   area.top_left.x,
+                   area.top_left.y,
+                   area.size.width,
+                   area.size.height,
+                   DIALOG_BORDER);
+
+    let parts = column(padded(area, 2), &[Length(lines_h), Length(BUTTON_H)], 1);
+
+    for (i, line) in spec.lines.iter().enumerate() {
+        label(platform,
+              column(parts[0], &vec![Length(1); spec.lines.len()], 0)[i],
+              line,
+              Anchor::Left);
+    }
+
+    let row_rect = anchored(parts[1], Size::new(buttons_w, BUTTON_H), Anchor::Center);
+    let constraints: Vec<Constraint> = button_widths.iter().map(|&w| Length(w)).collect();
+    let button_rects = row(row_rect, &constraints, 1);
+
+    context.begin_dialog();
+
+    let ids: Vec<UiId> = spec.buttons.iter().map(|text| child_id(spec.id, text)).collect();
+    if !ids.contains(&context.focused) {
+        context.focused = ids.first().cloned().unwrap_or(0);
+    }
+
+    let mut result = None;
+    for (index, (&text, &rect)) in spec.buttons.iter().zip(button_rects.iter()).enumerate() {
+        let button_spec = button_in(rect, text, ids[index]);
+
+        if do_button(platform,
+                     context,
+                     &button_spec,
+                     input.left_mouse_pressed,
+                     input.left_mouse_released) {
+            result = Some(index);
+        }
+    }
+
+    context.end_dialog();
+
+    result
+}
+
+pub struct TextFieldSpec {
+    pub x: i32,
+    pub y: i32,
+    //which characters can be typed in
+    pub allowed: fn(char) -> bool,
+    pub id: i32,
+}
+
+//Applies the typing and caret movement since the last frame to `field`,
+//skipping characters the spec doesn't allow, and draws it.
+//Clicking puts the caret where the mouse is, and dragging selects.
+pub fn text_field(platform: &Platform,
+                  context: &mut UIContext,
+                  spec: &TextFieldSpec,
+                  field: &mut TextField,
+                  input: &Input) {
+    let TextFieldSpec { x, y, allowed, id } = *spec;
+
+    for &edit in input.edits.iter() {
+        match edit {
+            Edit::Insert(c) => {
+                if allowed(c) {
+                    field.insert(c);
+                }
+            }
+            Edit::Backspace => field.backspace(),
+            Edit::Delete => field.delete(),
+            Edit::Left(select) => field.left(select),
+            Edit::Right(select) => field.right(select),
+            Edit::Home(select) => field.home(select),
+            Edit::End(select) => field.end(select),
+        }
+    }
+
+    context.claim(id);
+
+    //one past the end, for the caret
+    let w = field.len() as i32 + 1;
+    let mouse_pos = (platform.mouse_position)();
+    let inside = inside_rect(mouse_pos, x, y, w, 1);
+    let column = (mouse_pos.x - x).max(0) as usize;
+
+    if !context.interactive() {
+        //the text still gets drawn
+    } else if context.active == id {
+        if input.left_mouse_released {
+            context.set_not_active();
+        } else {
+            field.move_caret(column, true);
+        }
+    } else if context.hot == id && input.left_mouse_pressed {
+        context.set_active(id);
+        field.move_caret(column, false);
+    }
+
+    if inside && context.interactive() {
+        context.set_next_hot(id);
+    }
+
+    let selection = field.selection();
+
+    for (i, c) in field.as_str().chars().chain(Some(' ')).enumerate() {
+        let selected = selection.map(|(start, end)| start <= i && i < end).unwrap_or(false);
+
+        let highlight = if i == field.caret() {
+            Some(CARET_COLOUR)
+        } else if selected {
+            Some(SELECTION_COLOUR)
+        } else {
+            None
+        };
+
+        match highlight {
+            Some(colour) => {
+                let bg = (platform.get_background)();
+
+                (platform.set_background)(colour);
+                (platform.print_xy)(x + i as i32, y, &c.to_string());
+                (platform.set_background)(bg);
+            }
+            None => (platform.print_xy)(x + i as i32, y, &c.to_string()),
+        }
+    }
+}
+
+//prints the text of a widget without a border, showing whether it's hot or
+//focused with colours instead
+fn print_highlighted(platform: &Platform,
+                     context: &UIContext,
+                     id: UiId,
+                     x: i32,
+                     y: i32,
+                     text: &str) {
+    let (fg, bg) = (platform.get_colors)();
+
+    if context.hot == id {
+        (platform.set_foreground)(HOT_COLOUR);
+    }
+    if context.focused == id {
+        (platform.set_background)(FOCUS_COLOUR);
+    }
+
+    (platform.print_xy)(x, y, text);
+
+    (platform.set_colors)(fg, bg);
+}
+
+pub fn inside_rect(point: Point, x: i32, y: i32, w: i32, h: i32) -> bool {
+    x <= point.x && y <= point.y && point.x < x + w && point.y < y + h
+}
+
+pub fn print_centered_line(platform: &Platform, x: i32, y: i32, w: i32, h: i32, text: &str) {
+    let x_ = {
+        let rect_middle = x + (w / 2);
+
+        rect_middle - (text.chars().count() as f32 / 2.0) as i32
+    };
+
+    let y_ = y + (h / 2);
+
+    (platform.print_xy)(x_, y_, &text);
+}
+
+//prints `text` on the given line of `rect`, counting from 0
+pub fn print_line(platform: &Platform, rect: Rect, line: i32, text: &str) {
+    (platform.print_xy)(rect.top_left.x, rect.top_left.y + line, text);
+}
+
+pub fn draw_rect_with(platform: &Platform, x: i32, y: i32, w: i32, h: i32, edges: [&str; 8]) {
+    (platform.clear)(Some(Rect::from_values(x, y, w, h)));
+
+    let right = x + w - 1;
+    let bottom = y + h - 1;
+    // top
+    (platform.print_xy)(x, y, edges[0]);
+    for i in (x + 1)..right {
+        (platform.print_xy)(i, y, edges[1]);
+    }
+    (platform.print_xy)(right, y, edges[2]);
+
+    // sides
+    for i in (y + 1)..bottom {
+        (platform.print_xy)(x, i, edges[3]);
+        (platform.print_xy)(right, i, edges[4]);
+    }
+
+    //bottom
+    (platform.print_xy)(x, bottom, edges[5]);
+    for i in (x + 1)..right {
+        (platform.print_xy)(i, bottom, edges[6]);
+    }
+    (platform.print_xy)(right, bottom, edges[7]);
+}